@@ -7,13 +7,36 @@ pub enum Action {
     MoveDown,
     MoveStoryLeft,
     MoveStoryRight,
+    MoveCardUp,
+    MoveCardDown,
     OpenDetail,
     CloseDetail,
     NewStory,
     DeleteStory,
     EditStoryTitle,
     EditStoryBody,
+    SwitchDetailFocus,
+    NewTask,
+    ToggleTask,
+    RenameTask,
+    DeleteTask,
+    MoveTaskUp,
+    MoveTaskDown,
     OpenEpicList,
+    NewEpic,
+    RenameEpic,
+    RecolorEpic,
+    DeleteEpic,
+    OpenBoardList,
+    RaisePriority,
+    LowerPriority,
+    OpenEpicPicker,
+    Search,
+    Filter,
+    CycleSort,
+    ClearSearch,
+    Undo,
+    Redo,
     ConfirmYes,
     ConfirmNo,
     InputChar(char),
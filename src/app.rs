@@ -1,10 +1,14 @@
-use crate::models::{Epic, Story};
+use crate::models::{Board, Column, Epic, Filter, StatusChange, Story, Task};
+use crate::undo::UndoStack;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     Board,
     Detail,
     EpicList,
+    BoardList,
+    /// Popup assigning an epic to the focused story.
+    EpicPicker,
     Input(InputTarget),
     Confirm(ConfirmAction),
 }
@@ -13,25 +17,77 @@ pub enum Mode {
 pub enum InputTarget {
     NewStory,
     EditStoryTitle,
+    NewTask,
+    RenameTask,
+    NewEpic,
+    RenameEpic,
+    Search,
+    Filter,
+}
+
+impl InputTarget {
+    /// The view the input is opened from, and returns to.
+    pub fn origin(self) -> Mode {
+        match self {
+            InputTarget::EditStoryTitle | InputTarget::NewTask | InputTarget::RenameTask => Mode::Detail,
+            InputTarget::NewEpic | InputTarget::RenameEpic => Mode::EpicList,
+            InputTarget::NewStory | InputTarget::Search | InputTarget::Filter => Mode::Board,
+        }
+    }
+}
+
+/// Which pane of the detail view receives navigation keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailFocus {
+    Body,
+    Tasks,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfirmAction {
     DeleteStory,
+    DeleteEpic,
+}
+
+impl ConfirmAction {
+    /// The view the prompt is opened from, and returns to.
+    pub fn origin(self) -> Mode {
+        match self {
+            ConfirmAction::DeleteStory => Mode::Board,
+            ConfirmAction::DeleteEpic => Mode::EpicList,
+        }
+    }
 }
 
 pub struct App {
     pub mode: Mode,
+    pub db_path: String,
+    pub board_id: i64,
+    pub boards: Vec<Board>,
     pub selected_column: usize,
-    pub selected_card: [usize; 4],
+    pub selected_card: Vec<usize>,
     pub list_selection: usize,
     pub scroll_offset: u16,
     pub epic_filter: Option<i64>,
-    pub columns: [Vec<Story>; 4],
+    pub search_query: Option<String>,
+    /// Story ids matching `search_query`, best match first.
+    pub search_hits: Vec<i64>,
+    /// Live `--where`-style filter narrowing the board's stories.
+    pub filter: Filter,
+    /// Workflow of the board; `columns` and `selected_card` are indexed in this order.
+    pub workflow: Vec<Column>,
+    pub columns: Vec<Vec<Story>>,
     pub epics: Vec<Epic>,
     pub current_story: Option<Story>,
+    pub status_history: Vec<StatusChange>,
+    /// Checklist of `current_story`, in display order.
+    pub tasks: Vec<Task>,
+    pub detail_focus: DetailFocus,
+    pub task_cursor: usize,
     pub input_buffer: String,
     pub status_message: Option<String>,
+    /// Survives refreshes, so external changes don't discard it.
+    pub undo: UndoStack,
     pub should_quit: bool,
 }
 
@@ -39,22 +95,34 @@ impl App {
     pub fn new() -> Self {
         App {
             mode: Mode::Board,
+            db_path: String::new(),
+            board_id: 0,
+            boards: vec![],
             selected_column: 0,
-            selected_card: [0; 4],
+            selected_card: vec![],
             list_selection: 0,
             scroll_offset: 0,
             epic_filter: None,
-            columns: [vec![], vec![], vec![], vec![]],
+            search_query: None,
+            search_hits: vec![],
+            filter: Filter::default(),
+            workflow: vec![],
+            columns: vec![],
             epics: vec![],
             current_story: None,
+            status_history: vec![],
+            tasks: vec![],
+            detail_focus: DetailFocus::Body,
+            task_cursor: 0,
             input_buffer: String::new(),
             status_message: None,
+            undo: UndoStack::default(),
             should_quit: false,
         }
     }
 
     pub fn move_column_right(&mut self) {
-        if self.selected_column < 3 {
+        if self.selected_column + 1 < self.columns.len() {
             self.selected_column += 1;
         }
     }
@@ -67,7 +135,7 @@ impl App {
 
     pub fn move_card_down(&mut self) {
         let col = self.selected_column;
-        let len = self.columns[col].len();
+        let len = self.columns.get(col).map_or(0, |c| c.len());
         if len > 0 && self.selected_card[col] < len - 1 {
             self.selected_card[col] += 1;
         }
@@ -75,18 +143,57 @@ impl App {
 
     pub fn move_card_up(&mut self) {
         let col = self.selected_column;
-        if self.selected_card[col] > 0 {
-            self.selected_card[col] -= 1;
+        if let Some(card) = self.selected_card.get_mut(col) {
+            *card = card.saturating_sub(1);
         }
     }
 
     pub fn selected_story(&self) -> Option<&Story> {
         let col = self.selected_column;
-        self.columns[col].get(self.selected_card[col])
+        self.columns.get(col)?.get(self.selected_card[col])
+    }
+
+    /// Move the cursor to the card with the given story id, if it is on the board.
+    pub fn select_story(&mut self, id: i64) -> bool {
+        for (col, stories) in self.columns.iter().enumerate() {
+            if let Some(card) = stories.iter().position(|s| s.id == id) {
+                self.selected_column = col;
+                self.selected_card[col] = card;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Story the detail view is showing, or else the selected card.
+    pub fn focused_story(&self) -> Option<&Story> {
+        self.current_story.as_ref().or_else(|| self.selected_story())
+    }
+
+    /// Epic under the cursor in the epic list or picker; row 0 is "All Epics"/"No epic".
+    pub fn selected_epic(&self) -> Option<&Epic> {
+        self.list_selection.checked_sub(1).and_then(|i| self.epics.get(i))
+    }
+
+    pub fn selected_task(&self) -> Option<&Task> {
+        self.tasks.get(self.task_cursor)
+    }
+
+    pub fn move_task_cursor_down(&mut self) {
+        if self.task_cursor + 1 < self.tasks.len() {
+            self.task_cursor += 1;
+        }
+    }
+
+    pub fn move_task_cursor_up(&mut self) {
+        self.task_cursor = self.task_cursor.saturating_sub(1);
     }
 
     pub fn clamp_selections(&mut self) {
-        for i in 0..4 {
+        self.task_cursor = self.task_cursor.min(self.tasks.len().saturating_sub(1));
+        self.selected_card.resize(self.columns.len(), 0);
+        self.selected_column = self.selected_column.min(self.columns.len().saturating_sub(1));
+        for i in 0..self.columns.len() {
             let len = self.columns[i].len();
             if len == 0 {
                 self.selected_card[i] = 0;
@@ -109,9 +216,16 @@ mod tests {
         assert!(app.epic_filter.is_none());
     }
 
+    fn app_with_columns(n: usize) -> App {
+        let mut app = App::new();
+        app.columns = vec![vec![]; n];
+        app.clamp_selections();
+        app
+    }
+
     #[test]
     fn column_navigation_wraps() {
-        let mut app = App::new();
+        let mut app = app_with_columns(4);
         app.move_column_right();
         assert_eq!(app.selected_column, 1);
         app.selected_column = 3;
@@ -119,13 +233,69 @@ mod tests {
         assert_eq!(app.selected_column, 3);
     }
 
+    #[test]
+    fn column_navigation_follows_column_count() {
+        let mut app = app_with_columns(6);
+        for _ in 0..10 {
+            app.move_column_right();
+        }
+        assert_eq!(app.selected_column, 5);
+        app.columns.truncate(2);
+        app.clamp_selections();
+        assert_eq!(app.selected_column, 1);
+        assert_eq!(app.selected_card.len(), 2);
+    }
+
     #[test]
     fn column_navigation_left_clamps() {
-        let mut app = App::new();
+        let mut app = app_with_columns(4);
         app.move_column_left();
         assert_eq!(app.selected_column, 0);
     }
 
+    #[test]
+    fn select_story_finds_card_in_any_column() {
+        use crate::models::{Priority, Status};
+        let story = |id| Story {
+            id,
+            epic_id: None,
+            title: String::new(),
+            description: String::new(),
+            status: Status::new("in_review"),
+            priority: Priority::Medium,
+            created_at: String::new(),
+            updated_at: String::new(),
+            rank: id as f64,
+            labels: vec![],
+            blocked: false,
+            blocked_by: vec![],
+            task_count: 0,
+            tasks_done: 0,
+            claim: None,
+        };
+        let mut app = app_with_columns(4);
+        app.columns[2] = vec![story(4), story(9)];
+        assert!(app.select_story(9));
+        assert_eq!(app.selected_column, 2);
+        assert_eq!(app.selected_card[2], 1);
+        assert!(!app.select_story(5));
+    }
+
+    #[test]
+    fn task_cursor_stays_in_range() {
+        let task = |id| Task { id, story_id: 1, title: String::new(), done: false, sort_order: id };
+        let mut app = App::new();
+        app.tasks = vec![task(1), task(2)];
+        app.move_task_cursor_down();
+        app.move_task_cursor_down();
+        assert_eq!(app.selected_task().map(|t| t.id), Some(2));
+        app.tasks.pop();
+        app.clamp_selections();
+        assert_eq!(app.task_cursor, 0);
+        app.move_task_cursor_up();
+        assert_eq!(app.selected_task().map(|t| t.id), Some(1));
+    }
+
     #[test]
     fn mode_transitions() {
         let mut app = App::new();
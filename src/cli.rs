@@ -1,15 +1,29 @@
-use clap::{Parser, Subcommand};
-use crate::models::{Status, Priority};
+use std::path::PathBuf;
+use clap::{ArgGroup, Parser, Subcommand};
+use crate::color::parse_color_arg;
+use crate::models::{parse_label, parse_ttl, Filter, Sort, Status, Priority};
+use crate::output::Format;
 
 #[derive(Parser)]
 #[command(name = "stack", version, about = "Terminal kanban board for personal task tracking")]
 pub struct Cli {
+    /// Board to use (name or ID); defaults to the last board switched to
+    #[arg(long, global = true)]
+    pub board: Option<String>,
+    /// Database file to open; overrides STACK_DB and .stack discovery
+    #[arg(long, global = true)]
+    pub db: Option<PathBuf>,
+    /// Output format; tables at a terminal, JSON otherwise
+    #[arg(long, global = true, value_enum)]
+    pub format: Option<Format>,
     #[command(subcommand)]
     pub command: Option<Command>,
 }
 
 #[derive(Subcommand)]
 pub enum Command {
+    /// Create a repository-local board in .stack/ under the current directory
+    Init,
     /// Manage epics
     Epic {
         #[command(subcommand)]
@@ -25,14 +39,77 @@ pub enum Command {
         #[command(subcommand)]
         action: TaskAction,
     },
-    /// Show the full board state
+    /// Manage the board's workflow columns
+    Column {
+        #[command(subcommand)]
+        action: ColumnAction,
+    },
+    /// Manage story labels
+    Label {
+        #[command(subcommand)]
+        action: LabelAction,
+    },
+    /// Show the full board state, or configure the board
     Board {
+        #[command(subcommand)]
+        action: Option<BoardAction>,
         /// Filter by epic ID
         #[arg(long)]
         epic: Option<i64>,
+        /// Filter by label
+        #[arg(long, value_parser = parse_label)]
+        label: Option<String>,
+        /// Draw the board as the TUI shows it, as text (ANSI at a terminal)
+        #[arg(long)]
+        render: bool,
+        /// Width to draw at; defaults to the terminal width, or 120
+        #[arg(long, requires = "render", value_parser = clap::value_parser!(u16).range(20..))]
+        width: Option<u16>,
+    },
+    /// Inspect and upgrade the database schema
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
     },
 }
 
+#[derive(Subcommand)]
+pub enum BoardAction {
+    /// Create a new board with the default columns
+    Create {
+        name: String,
+    },
+    /// List all boards
+    List,
+    /// Make a board the default for the CLI and TUI
+    Switch {
+        /// Board name or ID
+        name: String,
+    },
+    /// Set a column's work-in-progress limit (0 removes it)
+    Limit {
+        status: Status,
+        limit: u32,
+    },
+    /// Refuse (true) or only warn about (false) moves that exceed a WIP limit
+    Strict {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DbAction {
+    /// Apply pending schema migrations
+    Migrate {
+        /// List pending migrations without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show the current and latest schema versions
+    Version,
+}
+
 #[derive(Subcommand)]
 pub enum EpicAction {
     /// Create a new epic
@@ -41,17 +118,55 @@ pub enum EpicAction {
         title: String,
         #[arg(long, default_value = "")]
         description: String,
-        #[arg(long, default_value = "white")]
+        /// Color name (e.g. red, light-blue) or #rrggbb
+        #[arg(long, default_value = "white", value_parser = parse_color_arg)]
         color: String,
     },
     /// List all epics
     List,
-    /// Delete an epic by ID
+    /// Show an epic with its story counts per column and percent complete
+    Show {
+        id: i64,
+    },
+    /// Update fields of an existing epic
+    Update {
+        id: i64,
+        #[arg(long)]
+        title: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Color name (e.g. red, light-blue) or #rrggbb
+        #[arg(long, value_parser = parse_color_arg)]
+        color: Option<String>,
+    },
+    /// Delete an epic by ID; its stories are left without an epic unless reassigned
     Delete {
         id: i64,
+        /// Move the epic's stories to this epic first
+        #[arg(long)]
+        reassign: Option<i64>,
     },
 }
 
+/// Value of `--epic` where an epic can also be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpicArg {
+    Id(i64),
+    None,
+}
+
+impl std::str::FromStr for EpicArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "none" => Ok(EpicArg::None),
+            id => id.parse().map(EpicArg::Id)
+                .map_err(|_| format!("invalid epic: '{}' (expected an epic ID or 'none')", s)),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum StoryAction {
     /// Create a new story
@@ -64,8 +179,12 @@ pub enum StoryAction {
         priority: Priority,
         #[arg(long, default_value = "")]
         body: String,
-        #[arg(long, default_value = "todo")]
-        status: Status,
+        /// Column key; defaults to the first column
+        #[arg(long)]
+        status: Option<Status>,
+        /// Label to attach (repeatable)
+        #[arg(long = "label", value_parser = parse_label)]
+        labels: Vec<String>,
     },
     /// List stories with optional filters
     List {
@@ -73,6 +192,45 @@ pub enum StoryAction {
         epic: Option<i64>,
         #[arg(long)]
         status: Option<Status>,
+        #[arg(long, value_parser = parse_label)]
+        label: Option<String>,
+        /// Filter expression, e.g. "priority>=high status:todo,in-progress updated<7d title~login"
+        #[arg(long = "where")]
+        filter: Option<Filter>,
+        /// Order by rank, id, priority, created, updated or title; append :desc to reverse
+        #[arg(long, default_value = "id")]
+        sort: Sort,
+        /// Return at most this many stories
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Skip this many stories
+        #[arg(long, conflicts_with = "cursor")]
+        offset: Option<usize>,
+        /// Continue from the `next_cursor` of a previous page
+        #[arg(long)]
+        cursor: Option<i64>,
+    },
+    /// Show the story to work on next: unblocked, most urgent, then highest ranked
+    Next {
+        #[arg(long)]
+        epic: Option<i64>,
+        #[arg(long, value_parser = parse_label)]
+        label: Option<String>,
+        /// Column to pick from; defaults to the one before the first started column
+        #[arg(long)]
+        from: Option<Status>,
+        /// Also move the story to a started column, atomically
+        #[arg(long)]
+        claim: bool,
+        /// Column to move a claimed story into; defaults to the first started column
+        #[arg(long, requires = "claim")]
+        to: Option<Status>,
+        /// Lease the claimed story to this owner as well
+        #[arg(long, requires = "claim")]
+        owner: Option<String>,
+        /// How long the lease lasts, e.g. 30m or 2h
+        #[arg(long, default_value = "30m", value_parser = parse_ttl)]
+        ttl: i64,
     },
     /// Get a story by ID
     Get {
@@ -89,13 +247,139 @@ pub enum StoryAction {
         priority: Option<Priority>,
         #[arg(long)]
         body: Option<String>,
+        /// Epic ID, or `none` to detach the story from its epic
+        #[arg(long, conflicts_with = "no_epic")]
+        epic: Option<EpicArg>,
+        /// Detach the story from its epic (same as `--epic none`)
         #[arg(long)]
-        epic: Option<i64>,
+        no_epic: bool,
+    },
+    /// Move a story before or after another in the board's manual order
+    #[command(group(ArgGroup::new("anchor").args(["before", "after"]).required(true)))]
+    Rank {
+        id: i64,
+        /// Story to place this one just above
+        #[arg(long)]
+        before: Option<i64>,
+        /// Story to place this one just below
+        #[arg(long)]
+        after: Option<i64>,
+    },
+    /// Lease a story so other agents leave it alone
+    Claim {
+        id: i64,
+        #[arg(long)]
+        owner: String,
+        /// How long the claim lasts, e.g. 30m or 2h
+        #[arg(long, default_value = "30m", value_parser = parse_ttl)]
+        ttl: i64,
+    },
+    /// Extend a claim you hold
+    Renew {
+        id: i64,
+        #[arg(long)]
+        owner: String,
+        /// New lifetime of the claim, counted from now
+        #[arg(long, default_value = "30m", value_parser = parse_ttl)]
+        ttl: i64,
+    },
+    /// Give up a claim you hold
+    Release {
+        id: i64,
+        #[arg(long)]
+        owner: String,
     },
     /// Delete a story by ID
     Delete {
         id: i64,
     },
+    /// Mark a story as blocking another
+    Link {
+        id: i64,
+        /// Story that cannot start until this one is done
+        #[arg(long)]
+        blocks: i64,
+    },
+    /// Remove a blocking relationship
+    Unlink {
+        id: i64,
+        #[arg(long)]
+        blocks: i64,
+    },
+    /// Show a story's status transitions
+    History {
+        id: i64,
+    },
+    /// Full-text search over titles, bodies and task titles
+    Search {
+        query: String,
+        /// Maximum number of results
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ColumnAction {
+    /// List columns in board order
+    List,
+    /// Add a column
+    Add {
+        /// Key used in `--status`, e.g. `qa`
+        key: Status,
+        #[arg(long)]
+        name: String,
+        /// Zero-based position; appended when omitted
+        #[arg(long)]
+        position: Option<usize>,
+        /// Stories in this column count as finished
+        #[arg(long)]
+        done: bool,
+        /// Stories in this column are being worked on
+        #[arg(long, conflicts_with = "done")]
+        started: bool,
+    },
+    /// Rename a column, change its done and started flags or the order of its cards
+    Update {
+        key: Status,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        done: Option<bool>,
+        #[arg(long)]
+        started: Option<bool>,
+        /// Board order of the column's cards, e.g. priority:desc
+        #[arg(long)]
+        sort: Option<Sort>,
+    },
+    /// Move a column to a zero-based position
+    Move {
+        key: Status,
+        #[arg(long)]
+        to: usize,
+    },
+    /// Remove an empty column
+    Remove {
+        key: Status,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LabelAction {
+    /// List all labels with their story counts
+    List,
+    /// Add labels to a story
+    Add {
+        story_id: i64,
+        #[arg(required = true, value_parser = parse_label)]
+        names: Vec<String>,
+    },
+    /// Remove labels from a story
+    Remove {
+        story_id: i64,
+        #[arg(required = true, value_parser = parse_label)]
+        names: Vec<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -110,10 +394,28 @@ pub enum TaskAction {
     List {
         story_id: i64,
     },
+    /// Change a task's title
+    Update {
+        id: i64,
+        #[arg(long)]
+        title: String,
+    },
+    /// Move a task to a zero-based position within its story
+    Move {
+        id: i64,
+        #[arg(long)]
+        to: usize,
+    },
     /// Toggle a task's done status
     Toggle {
         id: i64,
     },
+    /// Mark a task done or not done (safe to retry, unlike toggle)
+    Set {
+        id: i64,
+        #[arg(long, action = clap::ArgAction::Set)]
+        done: bool,
+    },
     /// Delete a task by ID
     Delete {
         id: i64,
@@ -1,82 +1,218 @@
-use std::collections::HashMap;
+use crate::cli::{BoardAction, ColumnAction, Command, DbAction, EpicAction, EpicArg, LabelAction, StoryAction, TaskAction};
+use crate::db::{Database, DEFAULT_BOARD_ID, MIGRATIONS};
+use crate::error::StackError;
+use crate::models::{Filter, Page, Status, StoryPatch, Term};
 
-use crate::cli::{Command, EpicAction, StoryAction, TaskAction};
-use crate::db::Database;
-use crate::models::Status;
+/// A command's result, plus the cursor of the next page when a paginated
+/// listing has more to come.
+pub struct Reply {
+    pub result: serde_json::Value,
+    pub next_cursor: Option<String>,
+}
 
-pub fn run(command: Command, db: &Database) -> Result<(), Box<dyn std::error::Error>> {
-    let result = match command {
+impl From<serde_json::Value> for Reply {
+    fn from(result: serde_json::Value) -> Self {
+        Reply { result, next_cursor: None }
+    }
+}
+
+pub fn run(command: Command, db: &mut Database) -> Result<Reply, StackError> {
+    Ok(match command {
         Command::Epic { action } => handle_epic(action, db)?,
-        Command::Story { action } => handle_story(action, db)?,
+        Command::Story { action } => return handle_story(action, db),
         Command::Task { action } => handle_task(action, db)?,
-        Command::Board { epic } => handle_board(epic, db)?,
-    };
-    println!("{}", serde_json::to_string(&serde_json::json!({ "result": result }))?);
-    Ok(())
+        Command::Column { action } => handle_column(action, db)?,
+        Command::Label { action } => handle_label(action, db)?,
+        Command::Board { action: Some(action), .. } => handle_board_action(action, db)?,
+        Command::Board { action: None, epic, label, .. } => handle_board(epic, label.as_deref(), db)?,
+        Command::Db { action } => handle_db(action, db)?,
+        Command::Init => return Err(StackError::Validation("init runs before a database is opened".to_string())),
+    }.into())
 }
 
-fn handle_epic(action: EpicAction, db: &Database) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+fn handle_epic(action: EpicAction, db: &Database) -> Result<serde_json::Value, StackError> {
     match action {
         EpicAction::Create { title, description, color } => {
             let id = db.create_epic(&title, &description, &color)?;
-            let epics = db.list_epics()?;
-            let epic = epics.into_iter().find(|e| e.id == id)
-                .ok_or("Failed to retrieve created epic")?;
-            Ok(serde_json::to_value(epic)?)
+            Ok(serde_json::to_value(db.get_epic(id)?)?)
         }
         EpicAction::List => {
             let epics = db.list_epics()?;
             Ok(serde_json::to_value(epics)?)
         }
-        EpicAction::Delete { id } => {
-            db.delete_epic(id)?;
-            Ok(serde_json::json!({ "deleted": id }))
+        EpicAction::Show { id } => {
+            let epic = db.get_epic(id)?;
+            let counts: serde_json::Map<String, serde_json::Value> = db.epic_status_counts(id)?.into_iter()
+                .map(|(status, count)| (status.key().to_string(), count.into()))
+                .collect();
+            let mut value = serde_json::to_value(&epic)?;
+            value["status_counts"] = counts.into();
+            value["percent_complete"] = epic.percent_complete().into();
+            Ok(value)
+        }
+        EpicAction::Update { id, title, description, color } => {
+            db.update_epic(id, title.as_deref(), description.as_deref(), color.as_deref())?;
+            Ok(serde_json::to_value(db.get_epic(id)?)?)
+        }
+        EpicAction::Delete { id, reassign } => {
+            check_epic(db, id)?;
+            if let Some(target) = reassign {
+                check_epic(db, target)?;
+                if target == id {
+                    return Err(StackError::Validation("cannot reassign an epic's stories to itself".to_string()));
+                }
+            }
+            let moved = db.delete_epic(id, reassign)?;
+            Ok(serde_json::json!({ "deleted": id, "reassigned": moved }))
         }
     }
 }
 
-fn handle_story(action: StoryAction, db: &Database) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+fn handle_story(action: StoryAction, db: &Database) -> Result<Reply, StackError> {
     match action {
-        StoryAction::Create { title, epic, priority, body, status } => {
-            let id = db.create_story(&title, &body, epic, status, priority)?;
+        StoryAction::Create { title, epic, priority, body, status, labels } => {
+            let status = match status {
+                Some(s) => check_status(db, s)?,
+                None => db.list_columns()?.into_iter().next()
+                    .ok_or_else(|| StackError::Conflict("board has no columns".to_string()))?.key,
+            };
+            let epic = epic.map(|e| check_epic(db, e)).transpose()?;
+            let id = db.create_story(&title, &body, epic, &status, priority)?;
+            for label in &labels {
+                db.add_label(id, label)?;
+            }
             let story = db.get_story(id)?;
-            Ok(serde_json::to_value(story)?)
+            Ok(serde_json::to_value(story)?.into())
         }
-        StoryAction::List { epic, status } => {
-            let stories = db.list_stories(status, epic)?;
-            Ok(serde_json::to_value(stories)?)
+        StoryAction::List { epic, status, label, filter, sort, limit, offset, cursor } => {
+            let status = status.map(|s| check_status(db, s)).transpose()?;
+            let filter = filter.unwrap_or_default();
+            for term in &filter.terms {
+                if let Term::Status(statuses) = term {
+                    for s in statuses {
+                        check_status(db, s.clone())?;
+                    }
+                }
+            }
+            if let Some(id) = cursor
+                && let Err(e) = db.get_story(id)
+            {
+                return Err(match e {
+                    StackError::NotFound { .. } => StackError::Validation(format!("invalid cursor: {}", id)),
+                    e => e,
+                });
+            }
+            // Fetch one extra story to learn whether another page follows
+            let page = Page { limit: limit.map(|l| l + 1), offset: offset.unwrap_or(0), after: cursor };
+            let mut stories = db.list_stories(status.as_ref(), epic, label.as_deref(), &filter, sort, page)?;
+            let next_cursor = match limit {
+                Some(limit) if stories.len() > limit => {
+                    stories.truncate(limit);
+                    stories.last().map(|s| s.id.to_string())
+                }
+                _ => None,
+            };
+            Ok(Reply { result: serde_json::to_value(stories)?, next_cursor })
+        }
+        StoryAction::Next { epic, label, from, claim, to, owner, ttl } => {
+            let epic = epic.map(|e| check_epic(db, e)).transpose()?;
+            // Stories wait in the column before the first started one (To Do)
+            // and work on them begins in that started column (In Progress)
+            let columns = db.list_columns()?;
+            let start = columns.iter().position(|c| c.started);
+            let from = &match from {
+                Some(s) => check_status(db, s)?,
+                None => start.and_then(|i| i.checked_sub(1)).map(|i| columns[i].key.clone())
+                    .ok_or_else(|| StackError::Conflict("board has no column before its first started column; pass --from".to_string()))?,
+            };
+            let story = if claim {
+                let to = &match to {
+                    Some(s) => check_status(db, s)?,
+                    None => start.map(|i| columns[i].key.clone())
+                        .ok_or_else(|| StackError::Conflict("board has no started column; pass --to".to_string()))?,
+                };
+                let lease = owner.as_deref().map(|owner| (owner, ttl));
+                let story = db.claim_next_story(from, epic, label.as_deref(), to, lease)?;
+                if let Some(story) = &story
+                    && let Some(violation) = db.wip_violation(story.id, to)?
+                {
+                    eprintln!("{}", serde_json::json!({ "warning": violation }));
+                }
+                story
+            } else {
+                db.next_story(from, epic, label.as_deref())?
+            };
+            Ok(serde_json::to_value(story)?.into())
         }
         StoryAction::Get { id } => {
             let story = db.get_story(id)?;
-            Ok(serde_json::to_value(story)?)
+            Ok(serde_json::to_value(story)?.into())
         }
-        StoryAction::Update { id, title, status, priority, body, epic } => {
-            if let Some(t) = title {
-                db.update_story_title(id, &t)?;
-            }
-            if let Some(s) = status {
-                db.update_story_status(id, s)?;
-            }
-            if let Some(p) = priority {
-                db.update_story_priority(id, p)?;
+        StoryAction::Update { id, title, status, priority, body, epic, no_epic } => {
+            let epic_id = match (epic, no_epic) {
+                (Some(EpicArg::Id(e)), _) => Some(Some(e)),
+                (Some(EpicArg::None), _) | (None, true) => Some(None),
+                (None, false) => None,
+            };
+            let status = status.map(|s| check_status(db, s)).transpose()?;
+            let patch = StoryPatch { title, description: body, status, priority, epic_id };
+            // Strict WIP limits are enforced by `update_story`; otherwise only warn
+            let story = db.update_story(id, &patch)?;
+            if let Some(s) = &patch.status
+                && let Some(violation) = db.wip_violation(id, s)?
+            {
+                eprintln!("{}", serde_json::json!({ "warning": violation }));
             }
-            if let Some(b) = body {
-                db.update_story_description(id, &b)?;
-            }
-            if let Some(e) = epic {
-                db.update_story_epic(id, Some(e))?;
-            }
-            let story = db.get_story(id)?;
-            Ok(serde_json::to_value(story)?)
+            Ok(serde_json::to_value(story)?.into())
+        }
+        StoryAction::Rank { id, before, after } => {
+            let (anchor, after) = before.map(|anchor| (anchor, false))
+                .or(after.map(|anchor| (anchor, true)))
+                .ok_or_else(|| StackError::Validation("pass --before or --after".to_string()))?;
+            db.rank_story(id, anchor, after)?;
+            Ok(serde_json::to_value(db.get_story(id)?)?.into())
+        }
+        StoryAction::Claim { id, owner, ttl } => {
+            Ok(serde_json::to_value(db.claim_story(id, &owner, ttl)?)?.into())
+        }
+        StoryAction::Renew { id, owner, ttl } => {
+            Ok(serde_json::to_value(db.renew_claim(id, &owner, ttl)?)?.into())
+        }
+        StoryAction::Release { id, owner } => {
+            Ok(serde_json::to_value(db.release_claim(id, &owner)?)?.into())
         }
         StoryAction::Delete { id } => {
             db.delete_story(id)?;
-            Ok(serde_json::json!({ "deleted": id }))
+            Ok(serde_json::json!({ "deleted": id }).into())
+        }
+        StoryAction::Link { id, blocks } => {
+            db.get_story(id)?;
+            db.get_story(blocks)?;
+            db.link_stories(id, blocks)?;
+            Ok(serde_json::to_value(db.get_story(blocks)?)?.into())
+        }
+        StoryAction::Unlink { id, blocks } => {
+            if !db.unlink_stories(id, blocks)? {
+                return Err(StackError::Conflict(format!("story {} does not block story {}", id, blocks)));
+            }
+            Ok(serde_json::to_value(db.get_story(blocks)?)?.into())
+        }
+        StoryAction::History { id } => {
+            db.get_story(id)?;
+            let history = db.story_history(id)?;
+            Ok(serde_json::to_value(history)?.into())
+        }
+        StoryAction::Search { query, limit } => {
+            let mut hits = db.search_stories(&query)?;
+            if let Some(limit) = limit {
+                hits.truncate(limit);
+            }
+            Ok(serde_json::to_value(hits)?.into())
         }
     }
 }
 
-fn handle_task(action: TaskAction, db: &Database) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+fn handle_task(action: TaskAction, db: &Database) -> Result<serde_json::Value, StackError> {
     match action {
         TaskAction::Create { story_id, title } => {
             let id = db.create_task(story_id, &title)?;
@@ -87,10 +223,22 @@ fn handle_task(action: TaskAction, db: &Database) -> Result<serde_json::Value, B
             let tasks = db.list_tasks(story_id)?;
             Ok(serde_json::to_value(tasks)?)
         }
+        TaskAction::Update { id, title } => {
+            let task = db.rename_task(id, &title)?;
+            Ok(serde_json::to_value(task)?)
+        }
+        TaskAction::Move { id, to } => {
+            let task = db.move_task(id, to)?;
+            Ok(serde_json::to_value(db.list_tasks(task.story_id)?)?)
+        }
         TaskAction::Toggle { id } => {
             let task = db.toggle_task(id)?;
             Ok(serde_json::to_value(task)?)
         }
+        TaskAction::Set { id, done } => {
+            let task = db.set_task_done(id, done)?;
+            Ok(serde_json::to_value(task)?)
+        }
         TaskAction::Delete { id } => {
             db.delete_task(id)?;
             Ok(serde_json::json!({ "deleted": id }))
@@ -98,17 +246,146 @@ fn handle_task(action: TaskAction, db: &Database) -> Result<serde_json::Value, B
     }
 }
 
-fn handle_board(epic: Option<i64>, db: &Database) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
-    let mut board: HashMap<&str, serde_json::Value> = HashMap::new();
-    for status in Status::all() {
-        let stories = db.list_stories_by_status(*status, epic)?;
-        let key = match status {
-            Status::ToDo => "todo",
-            Status::InProgress => "in_progress",
-            Status::InReview => "in_review",
-            Status::Done => "done",
-        };
-        board.insert(key, serde_json::to_value(stories)?);
+fn handle_column(action: ColumnAction, db: &Database) -> Result<serde_json::Value, StackError> {
+    match action {
+        ColumnAction::List => {
+            let columns = db.list_columns()?;
+            Ok(serde_json::to_value(columns)?)
+        }
+        ColumnAction::Add { key, name, position, done, started } => {
+            db.add_column(&key, &name, position, done, started)?;
+            Ok(serde_json::to_value(db.list_columns()?)?)
+        }
+        ColumnAction::Update { key, name, done, started, sort } => {
+            let key = check_status(db, key)?;
+            db.update_column(&key, name.as_deref(), done, started, sort)?;
+            Ok(serde_json::to_value(db.get_column(&key)?)?)
+        }
+        ColumnAction::Move { key, to } => {
+            let key = check_status(db, key)?;
+            db.move_column(&key, to)?;
+            Ok(serde_json::to_value(db.list_columns()?)?)
+        }
+        ColumnAction::Remove { key } => {
+            let key = check_status(db, key)?;
+            db.remove_column(&key)?;
+            Ok(serde_json::json!({ "deleted": key }))
+        }
+    }
+}
+
+/// Ensure `status` names one of the board's columns.
+fn check_status(db: &Database, status: Status) -> Result<Status, StackError> {
+    let columns = db.list_columns()?;
+    if columns.iter().any(|c| c.key == status) {
+        return Ok(status);
+    }
+    let keys: Vec<&str> = columns.iter().map(|c| c.key.key()).collect();
+    Err(StackError::Validation(format!("unknown status: '{}' (expected one of: {})", status, keys.join(", "))))
+}
+
+/// Ensure `id` names an epic on the current board.
+fn check_epic(db: &Database, id: i64) -> Result<i64, StackError> {
+    if db.list_epics()?.iter().any(|e| e.id == id) {
+        Ok(id)
+    } else {
+        Err(StackError::not_found("epic", id))
+    }
+}
+
+fn handle_label(action: LabelAction, db: &Database) -> Result<serde_json::Value, StackError> {
+    match action {
+        LabelAction::List => {
+            let labels = db.list_labels()?;
+            Ok(serde_json::to_value(labels)?)
+        }
+        LabelAction::Add { story_id, names } => {
+            db.get_story(story_id)?;
+            for name in &names {
+                db.add_label(story_id, name)?;
+            }
+            Ok(serde_json::to_value(db.get_story(story_id)?)?)
+        }
+        LabelAction::Remove { story_id, names } => {
+            db.get_story(story_id)?;
+            for name in &names {
+                db.remove_label(story_id, name)?;
+            }
+            Ok(serde_json::to_value(db.get_story(story_id)?)?)
+        }
+    }
+}
+
+fn handle_board_action(action: BoardAction, db: &Database) -> Result<serde_json::Value, StackError> {
+    match action {
+        BoardAction::Create { name } => {
+            let id = db.create_board(&name)?;
+            Ok(serde_json::to_value(db.find_board(&id.to_string())?)?)
+        }
+        BoardAction::List => {
+            let current = db.current_board()?.unwrap_or(DEFAULT_BOARD_ID);
+            let boards: Vec<serde_json::Value> = db.list_boards()?.into_iter()
+                .map(|b| {
+                    let is_current = b.id == current;
+                    let mut value = serde_json::to_value(b)?;
+                    value["current"] = serde_json::Value::Bool(is_current);
+                    Ok(value)
+                })
+                .collect::<Result<_, serde_json::Error>>()?;
+            Ok(serde_json::Value::Array(boards))
+        }
+        BoardAction::Switch { name } => {
+            let board = db.find_board(&name)?;
+            db.set_current_board(board.id)?;
+            Ok(serde_json::to_value(board)?)
+        }
+        BoardAction::Limit { status, limit } => {
+            let status = check_status(db, status)?;
+            db.set_wip_limit(&status, if limit == 0 { None } else { Some(limit) })?;
+            Ok(serde_json::to_value(db.get_column(&status)?)?)
+        }
+        BoardAction::Strict { enabled } => {
+            db.set_wip_strict(enabled)?;
+            Ok(serde_json::json!({ "wip_strict": enabled }))
+        }
+    }
+}
+
+fn handle_board(epic: Option<i64>, label: Option<&str>, db: &Database) -> Result<serde_json::Value, StackError> {
+    let mut board = Vec::new();
+    for column in db.list_columns()? {
+        let stories = db.list_stories(Some(&column.key), epic, label, &Filter::default(), column.sort, Page::default())?;
+        board.push(serde_json::json!({ "key": column.key, "name": column.name, "stories": stories }));
+    }
+    Ok(serde_json::Value::Array(board))
+}
+
+fn handle_db(action: DbAction, db: &mut Database) -> Result<serde_json::Value, StackError> {
+    match action {
+        DbAction::Migrate { dry_run } => {
+            let from = db.schema_version()?;
+            let versions = if dry_run {
+                db.pending_migrations()?
+            } else {
+                db.migrate()?
+            };
+            let migrations: Vec<serde_json::Value> = versions.iter()
+                .map(|v| serde_json::json!({ "version": v, "description": MIGRATIONS[v - 1].description }))
+                .collect();
+            Ok(serde_json::json!({
+                "from": from,
+                "to": if dry_run { MIGRATIONS.len() } else { db.schema_version()? },
+                "dry_run": dry_run,
+                "migrations": migrations,
+            }))
+        }
+        DbAction::Version => {
+            let version = db.schema_version()?;
+            Ok(serde_json::json!({
+                "version": version,
+                "latest": MIGRATIONS.len(),
+                "pending": db.pending_migrations()?.len(),
+            }))
+        }
     }
-    Ok(serde_json::to_value(board)?)
 }
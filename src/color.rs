@@ -0,0 +1,60 @@
+use ratatui::style::Color;
+
+/// Parse an epic color: a terminal color name (`red`, `light-blue`, `dark gray`)
+/// or a `#rrggbb` hex code.
+pub fn parse_color(s: &str) -> Result<Color, String> {
+    let s = s.trim();
+    let invalid = || format!("invalid color '{}': use a color name like 'red' or '#rrggbb'", s);
+    match s.strip_prefix('#') {
+        Some(hex) => {
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+            Ok(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+        }
+        // ratatui would also read "12" as a palette index; only names are accepted
+        None if s.chars().any(|c| c.is_ascii_digit()) => Err(invalid()),
+        None => s.parse().map_err(|_| invalid()),
+    }
+}
+
+/// Clap value parser for `--color`: validates and normalizes to lowercase.
+pub fn parse_color_arg(s: &str) -> Result<String, String> {
+    parse_color(s)?;
+    Ok(s.trim().to_lowercase())
+}
+
+/// Color to draw an epic in; values stored before validation fall back to white.
+pub fn epic_color(color: &str) -> Color {
+    parse_color(color).unwrap_or(Color::White)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_hex() {
+        assert_eq!(parse_color("red"), Ok(Color::Red));
+        assert_eq!(parse_color("Light-Blue"), Ok(Color::LightBlue));
+        assert_eq!(parse_color("dark gray"), Ok(Color::DarkGray));
+        assert_eq!(parse_color("#ff8000"), Ok(Color::Rgb(255, 128, 0)));
+        assert_eq!(parse_color(" #00AAff "), Ok(Color::Rgb(0, 170, 255)));
+    }
+
+    #[test]
+    fn rejects_invalid_colors() {
+        assert!(parse_color("blurple").is_err());
+        assert!(parse_color("#ff80").is_err());
+        assert!(parse_color("#gg0000").is_err());
+        assert!(parse_color("12").is_err());
+        assert!(parse_color("").is_err());
+    }
+
+    #[test]
+    fn normalizes_arguments() {
+        assert_eq!(parse_color_arg("#FF8000").unwrap(), "#ff8000");
+        assert_eq!(epic_color("nonsense"), Color::White);
+    }
+}
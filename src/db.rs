@@ -1,27 +1,15 @@
-use rusqlite::{Connection, Result};
-use crate::models::{Epic, Story, Task, Status, Priority};
+use rusqlite::{Connection, OptionalExtension, Result, Transaction, TransactionBehavior};
+use crate::error::StackError;
+use crate::models::{Board, Claim, Column, Comparison, Epic, EpicSnapshot, Filter, Label, Page, SearchHit, Sort, SortField, Story, StoryPatch, StorySnapshot, StatusChange, Task, Term, Status, Priority};
 
 pub struct Database {
     conn: Connection,
+    /// Board that board-scoped queries (columns, epics, story lists) operate on.
+    board_id: i64,
 }
 
-fn status_to_db(s: &Status) -> &'static str {
-    match s {
-        Status::ToDo => "todo",
-        Status::InProgress => "in_progress",
-        Status::InReview => "in_review",
-        Status::Done => "done",
-    }
-}
-
-fn status_from_db(s: &str) -> Status {
-    match s {
-        "in_progress" => Status::InProgress,
-        "in_review" => Status::InReview,
-        "done" => Status::Done,
-        _ => Status::ToDo,
-    }
-}
+/// Board created by the boards migration; it owns everything that existed before.
+pub const DEFAULT_BOARD_ID: i64 = 1;
 
 fn priority_to_db(p: &Priority) -> &'static str {
     match p {
@@ -41,16 +29,18 @@ fn priority_from_db(s: &str) -> Priority {
     }
 }
 
-impl Database {
-    pub fn open(path: &std::path::Path) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
-        Ok(Database { conn })
-    }
+pub struct Migration {
+    pub description: &'static str,
+    sql: &'static str,
+}
 
-    pub fn migrate(&mut self) -> Result<()> {
-        self.conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS epics (
+/// Schema migrations in order. Entry `n` upgrades a database from
+/// `user_version` n to n + 1; append new entries, never edit shipped ones.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        // Uses IF NOT EXISTS so databases created before versioning adopt it as-is.
+        description: "initial schema",
+        sql: "CREATE TABLE IF NOT EXISTS epics (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 title TEXT NOT NULL,
                 description TEXT NOT NULL DEFAULT '',
@@ -74,313 +64,1906 @@ impl Database {
                 title TEXT NOT NULL,
                 done INTEGER NOT NULL DEFAULT 0,
                 sort_order INTEGER NOT NULL DEFAULT 0
+            );",
+    },
+    Migration {
+        description: "story status history",
+        sql: "CREATE TABLE story_status_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                story_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+                from_status TEXT,
+                to_status TEXT NOT NULL,
+                changed_at TEXT NOT NULL DEFAULT (datetime('now'))
             );
-            -- Legacy table kept for migration compat; not used by app"
-        )?;
-        Ok(())
+            CREATE INDEX idx_story_status_history_story ON story_status_history(story_id, id);
+
+            -- Existing stories have been in their current status since at least their last update
+            INSERT INTO story_status_history (story_id, from_status, to_status, changed_at)
+                SELECT id, NULL, status, updated_at FROM stories ORDER BY id;",
+    },
+    Migration {
+        description: "full-text search index",
+        sql: "CREATE VIRTUAL TABLE story_search USING fts5(title, description, tasks);
+
+            INSERT INTO story_search (rowid, title, description, tasks)
+                SELECT id, title, description,
+                    (SELECT COALESCE(group_concat(title, ' '), '') FROM tasks WHERE story_id = stories.id)
+                FROM stories;
+
+            CREATE TRIGGER story_search_insert AFTER INSERT ON stories BEGIN
+                INSERT INTO story_search (rowid, title, description, tasks)
+                    VALUES (new.id, new.title, new.description, '');
+            END;
+            CREATE TRIGGER story_search_update AFTER UPDATE OF title, description ON stories BEGIN
+                UPDATE story_search SET title = new.title, description = new.description
+                    WHERE rowid = new.id;
+            END;
+            CREATE TRIGGER story_search_delete AFTER DELETE ON stories BEGIN
+                DELETE FROM story_search WHERE rowid = old.id;
+            END;
+
+            CREATE TRIGGER story_search_task_insert AFTER INSERT ON tasks BEGIN
+                UPDATE story_search
+                    SET tasks = (SELECT COALESCE(group_concat(title, ' '), '') FROM tasks WHERE story_id = new.story_id)
+                    WHERE rowid = new.story_id;
+            END;
+            CREATE TRIGGER story_search_task_update AFTER UPDATE OF title, story_id ON tasks BEGIN
+                UPDATE story_search
+                    SET tasks = (SELECT COALESCE(group_concat(title, ' '), '') FROM tasks WHERE story_id = story_search.rowid)
+                    WHERE rowid IN (old.story_id, new.story_id);
+            END;
+            CREATE TRIGGER story_search_task_delete AFTER DELETE ON tasks BEGIN
+                UPDATE story_search
+                    SET tasks = (SELECT COALESCE(group_concat(title, ' '), '') FROM tasks WHERE story_id = old.story_id)
+                    WHERE rowid = old.story_id;
+            END;",
+    },
+    Migration {
+        description: "story labels",
+        sql: "CREATE TABLE labels (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE
+            );
+
+            CREATE TABLE story_labels (
+                story_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+                label_id INTEGER NOT NULL REFERENCES labels(id) ON DELETE CASCADE,
+                PRIMARY KEY (story_id, label_id)
+            );
+            CREATE INDEX idx_story_labels_label ON story_labels(label_id);",
+    },
+    Migration {
+        description: "story dependencies",
+        sql: "CREATE TABLE story_dependencies (
+                blocker_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+                blocked_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+                PRIMARY KEY (blocker_id, blocked_id),
+                CHECK (blocker_id <> blocked_id)
+            );
+            CREATE INDEX idx_story_dependencies_blocked ON story_dependencies(blocked_id);",
+    },
+    Migration {
+        description: "configurable workflow columns",
+        sql: "CREATE TABLE workflow_columns (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key TEXT NOT NULL,
+                name TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                is_done INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE UNIQUE INDEX idx_workflow_columns_key ON workflow_columns(key);
+
+            INSERT INTO workflow_columns (key, name, position, is_done) VALUES
+                ('todo', 'To Do', 0, 0),
+                ('in_progress', 'In Progress', 1, 0),
+                ('in_review', 'In Review', 2, 0),
+                ('done', 'Done', 3, 1);
+
+            -- Unknown statuses used to be read back as To Do
+            UPDATE stories SET status = 'todo' WHERE status NOT IN (SELECT key FROM workflow_columns);
+
+            CREATE TRIGGER stories_status_insert_check BEFORE INSERT ON stories
+                WHEN NOT EXISTS (SELECT 1 FROM workflow_columns WHERE key = new.status)
+            BEGIN
+                SELECT RAISE(ABORT, 'unknown status: no workflow column has that key');
+            END;
+            CREATE TRIGGER stories_status_update_check BEFORE UPDATE OF status ON stories
+                WHEN NOT EXISTS (SELECT 1 FROM workflow_columns WHERE key = new.status)
+            BEGIN
+                SELECT RAISE(ABORT, 'unknown status: no workflow column has that key');
+            END;",
+    },
+    Migration {
+        description: "column WIP limits and settings",
+        sql: "ALTER TABLE workflow_columns ADD COLUMN wip_limit INTEGER;
+
+            CREATE TABLE settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+    },
+    Migration {
+        description: "multiple boards",
+        sql: "CREATE TABLE boards (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            INSERT INTO boards (id, name) VALUES (1, 'default');
+
+            ALTER TABLE stories ADD COLUMN board_id INTEGER NOT NULL DEFAULT 1;
+            ALTER TABLE epics ADD COLUMN board_id INTEGER NOT NULL DEFAULT 1;
+            ALTER TABLE workflow_columns ADD COLUMN board_id INTEGER NOT NULL DEFAULT 1;
+            CREATE INDEX idx_stories_board_status ON stories(board_id, status);
+            CREATE INDEX idx_epics_board ON epics(board_id);
+
+            DROP INDEX idx_workflow_columns_key;
+            CREATE UNIQUE INDEX idx_workflow_columns_board_key ON workflow_columns(board_id, key);
+
+            DROP TRIGGER stories_status_insert_check;
+            DROP TRIGGER stories_status_update_check;
+            CREATE TRIGGER stories_status_insert_check BEFORE INSERT ON stories
+                WHEN NOT EXISTS (SELECT 1 FROM workflow_columns WHERE board_id = new.board_id AND key = new.status)
+            BEGIN
+                SELECT RAISE(ABORT, 'unknown status: no workflow column has that key');
+            END;
+            CREATE TRIGGER stories_status_update_check BEFORE UPDATE OF status, board_id ON stories
+                WHEN NOT EXISTS (SELECT 1 FROM workflow_columns WHERE board_id = new.board_id AND key = new.status)
+            BEGIN
+                SELECT RAISE(ABORT, 'unknown status: no workflow column has that key');
+            END;",
+    },
+    Migration {
+        description: "per-column sort order",
+        sql: "ALTER TABLE workflow_columns ADD COLUMN sort TEXT NOT NULL DEFAULT 'id';",
+    },
+    Migration {
+        description: "manual story rank",
+        sql: "
+            ALTER TABLE stories ADD COLUMN rank REAL NOT NULL DEFAULT 0;
+            UPDATE stories SET rank = id;
+            CREATE INDEX idx_stories_rank ON stories(board_id, rank);
+            UPDATE workflow_columns SET sort = 'rank' WHERE sort = 'id';",
+    },
+    Migration {
+        description: "story claims",
+        sql: "
+            CREATE TABLE story_claims (
+                story_id INTEGER PRIMARY KEY REFERENCES stories(id) ON DELETE CASCADE,
+                owner TEXT NOT NULL,
+                expires_at TEXT NOT NULL
+            );",
+    },
+    Migration {
+        description: "started columns",
+        sql: "
+            ALTER TABLE workflow_columns ADD COLUMN is_started INTEGER NOT NULL DEFAULT 0;
+            UPDATE workflow_columns SET is_started = 1
+                WHERE NOT is_done AND key <> 'todo'
+                    AND position > (SELECT MIN(position) FROM workflow_columns first WHERE first.board_id = workflow_columns.board_id);",
+    },
+];
+
+/// Rejects a schema version written by a newer release than this one.
+fn known_version(version: usize) -> Result<usize, StackError> {
+    if version > MIGRATIONS.len() {
+        return Err(StackError::Conflict(format!(
+            "database schema version {} is newer than this stack supports ({}); upgrade stack",
+            version,
+            MIGRATIONS.len(),
+        )));
     }
+    Ok(version)
+}
 
-    // --- Epic CRUD ---
+/// Columns every new board starts with: (key, name, done, started).
+const DEFAULT_COLUMNS: &[(&str, &str, bool, bool)] = &[
+    ("todo", "To Do", false, false),
+    ("in_progress", "In Progress", false, true),
+    ("in_review", "In Review", false, true),
+    ("done", "Done", true, false),
+];
 
-    #[allow(dead_code)]
-    pub fn create_epic(&self, title: &str, description: &str, color: &str) -> Result<i64> {
-        self.conn.execute(
-            "INSERT INTO epics (title, description, color) VALUES (?1, ?2, ?3)",
-            rusqlite::params![title, description, color],
-        )?;
-        Ok(self.conn.last_insert_rowid())
+/// Quotes each term of a user query so FTS5 syntax characters are matched
+/// literally, and makes every term a prefix match.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() { None } else { Some(terms.join(" ")) }
+}
+
+const WIP_STRICT_SETTING: &str = "wip_strict";
+const CURRENT_BOARD_SETTING: &str = "current_board";
+
+const COLUMN_COLUMNS: &str = "id, key, name, position, is_done, wip_limit,
+    (SELECT COUNT(*) FROM stories
+        WHERE stories.board_id = workflow_columns.board_id AND stories.status = workflow_columns.key),
+    sort, is_started";
+
+fn column_from_row(row: &rusqlite::Row) -> Result<Column> {
+    let key: String = row.get(1)?;
+    Ok(Column {
+        id: row.get(0)?,
+        key: Status::new(&key),
+        name: row.get(2)?,
+        position: row.get(3)?,
+        done: row.get(4)?,
+        wip_limit: row.get(5)?,
+        story_count: row.get(6)?,
+        sort: row.get::<_, String>(7)?.parse().map_err(|e: String| {
+            rusqlite::Error::FromSqlConversionFailure(7, rusqlite::types::Type::Text, e.into())
+        })?,
+        started: row.get(8)?,
+    })
+}
+
+/// Place column `id` at index `to` (clamped) and renumber its board's positions.
+/// With `to == None` the column is dropped from the order instead.
+fn reorder_column(conn: &Connection, board_id: i64, id: i64, to: Option<usize>) -> Result<()> {
+    let mut ids: Vec<i64> = conn.prepare("SELECT id FROM workflow_columns WHERE board_id = ?1 AND id <> ?2 ORDER BY position")?
+        .query_map([board_id, id], |row| row.get(0))?
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(to) = to {
+        ids.insert(to.min(ids.len()), id);
     }
+    for (position, id) in ids.iter().enumerate() {
+        conn.execute("UPDATE workflow_columns SET position = ?1 WHERE id = ?2", rusqlite::params![position as i64, id])?;
+    }
+    Ok(())
+}
 
-    pub fn list_epics(&self) -> Result<Vec<Epic>> {
-        let mut stmt = self.conn.prepare("SELECT id, title, description, color FROM epics ORDER BY id")?;
-        let epics = stmt.query_map([], |row| {
-            Ok(Epic {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                color: row.get(3)?,
+const BOARD_COLUMNS: &str = "id, name, created_at,
+    (SELECT COUNT(*) FROM stories WHERE stories.board_id = boards.id)";
+
+fn board_from_row(row: &rusqlite::Row) -> Result<Board> {
+    Ok(Board {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+        story_count: row.get(3)?,
+    })
+}
+
+const EPIC_COLUMNS: &str = "id, title, description, color,
+    (SELECT COUNT(*) FROM stories WHERE stories.epic_id = epics.id),
+    (SELECT COUNT(*) FROM stories WHERE stories.epic_id = epics.id
+        AND status IN (SELECT key FROM workflow_columns WHERE board_id = stories.board_id AND is_done))";
+
+fn epic_from_row(row: &rusqlite::Row) -> Result<Epic> {
+    Ok(Epic {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        color: row.get(3)?,
+        story_count: row.get(4)?,
+        done_count: row.get(5)?,
+    })
+}
+
+const STORY_COLUMNS: &str = "id, epic_id, title, description, status, priority, created_at, updated_at,
+    (SELECT group_concat(name, ',') FROM (
+        SELECT labels.name FROM story_labels JOIN labels ON labels.id = story_labels.label_id
+        WHERE story_labels.story_id = stories.id ORDER BY labels.name
+    )) AS labels,
+    (SELECT group_concat(blocker.id, ',') FROM story_dependencies dep
+        JOIN stories blocker ON blocker.id = dep.blocker_id
+        WHERE dep.blocked_id = stories.id
+            AND blocker.status NOT IN (SELECT key FROM workflow_columns WHERE board_id = blocker.board_id AND is_done)
+    ) AS blocked_by,
+    (SELECT COUNT(*) FROM tasks WHERE story_id = stories.id) AS task_count,
+    (SELECT COUNT(*) FROM tasks WHERE story_id = stories.id AND done) AS tasks_done,
+    stories.rank,
+    (SELECT owner FROM story_claims WHERE story_id = stories.id AND expires_at > datetime('now')) AS claim_owner,
+    (SELECT expires_at FROM story_claims WHERE story_id = stories.id AND expires_at > datetime('now')) AS claim_expires_at";
+
+/// Stories with an unexpired claim, for excluding them from a query.
+const LIVE_CLAIM: &str = "EXISTS (SELECT 1 FROM story_claims WHERE story_id = stories.id AND expires_at > datetime('now'))";
+
+/// Sorts priorities by urgency, for comparing them in SQL.
+const PRIORITY_RANK: &str = "CASE priority WHEN 'low' THEN 0 WHEN 'medium' THEN 1 WHEN 'high' THEN 2 WHEN 'critical' THEN 3 END";
+
+fn priority_rank(p: Priority) -> i64 {
+    p as i64
+}
+
+/// Append a filter term to a story query as an `AND` clause, numbering its
+/// placeholders after the ones already in `params`.
+fn push_term(sql: &mut String, params: &mut Vec<Box<dyn rusqlite::types::ToSql>>, term: &Term) {
+    let mut placeholders = |values: Vec<Box<dyn rusqlite::types::ToSql>>| -> String {
+        let list: Vec<String> = values.into_iter()
+            .map(|v| {
+                params.push(v);
+                format!("?{}", params.len())
             })
-        })?.collect::<Result<Vec<_>>>()?;
-        Ok(epics)
+            .collect();
+        list.join(", ")
+    };
+    let like = |text: &str| -> Box<dyn rusqlite::types::ToSql> {
+        let escaped = text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        Box::new(format!("%{}%", escaped))
+    };
+    // `updated<7d` (younger than a week) means the timestamp is later than a week ago
+    let age = |op: &Comparison| match op {
+        Comparison::Lt => ">",
+        Comparison::Le => ">=",
+        Comparison::Gt => "<",
+        Comparison::Ge => "<=",
+    };
+    let clause = match term {
+        Term::Status(statuses) => format!("status IN ({})", placeholders(
+            statuses.iter().map(|s| Box::new(s.key().to_string()) as Box<dyn rusqlite::types::ToSql>).collect(),
+        )),
+        Term::Priority(priorities) => format!("priority IN ({})", placeholders(
+            priorities.iter().map(|p| Box::new(priority_to_db(p)) as Box<dyn rusqlite::types::ToSql>).collect(),
+        )),
+        Term::PriorityCompare(op, p) => format!("{} {} {}", PRIORITY_RANK, op.as_str(), placeholders(vec![Box::new(priority_rank(*p))])),
+        Term::Epic(ids) if ids.is_empty() => "epic_id IS NULL".to_string(),
+        Term::Epic(ids) => format!("epic_id IN ({})", placeholders(
+            ids.iter().map(|id| Box::new(*id) as Box<dyn rusqlite::types::ToSql>).collect(),
+        )),
+        Term::Label(names) => format!(
+            "id IN (SELECT story_id FROM story_labels JOIN labels ON labels.id = story_labels.label_id WHERE labels.name IN ({}))",
+            placeholders(names.iter().map(|n| Box::new(n.clone()) as Box<dyn rusqlite::types::ToSql>).collect()),
+        ),
+        Term::Title(text) => format!("title LIKE {} ESCAPE '\\'", placeholders(vec![like(text)])),
+        Term::Body(text) => format!("description LIKE {} ESCAPE '\\'", placeholders(vec![like(text)])),
+        Term::Updated(op, secs) => format!(
+            "updated_at {} datetime('now', {})", age(op), placeholders(vec![Box::new(format!("-{} seconds", secs))]),
+        ),
+        Term::Created(op, secs) => format!(
+            "created_at {} datetime('now', {})", age(op), placeholders(vec![Box::new(format!("-{} seconds", secs))]),
+        ),
+        Term::Blocked(blocked) => format!(
+            "{}EXISTS (SELECT 1 FROM story_dependencies dep JOIN stories blocker ON blocker.id = dep.blocker_id
+                WHERE dep.blocked_id = stories.id
+                    AND blocker.status NOT IN (SELECT key FROM workflow_columns WHERE board_id = blocker.board_id AND is_done))",
+            if *blocked { "" } else { "NOT " },
+        ),
+    };
+    sql.push_str(" AND ");
+    sql.push_str(&clause);
+}
+
+/// Maps a row selected with `STORY_COLUMNS` (extra columns may follow).
+fn story_from_row(row: &rusqlite::Row) -> Result<Story> {
+    let status_str: String = row.get(4)?;
+    let priority_str: String = row.get(5)?;
+    let labels: Option<String> = row.get(8)?;
+    let claim_owner: Option<String> = row.get(13)?;
+    let claim_expires_at: Option<String> = row.get(14)?;
+    let blocked_by: Vec<i64> = row.get::<_, Option<String>>(9)?
+        .map(|ids| ids.split(',').filter_map(|id| id.parse().ok()).collect())
+        .unwrap_or_default();
+    Ok(Story {
+        id: row.get(0)?,
+        epic_id: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        status: Status::new(&status_str),
+        priority: priority_from_db(&priority_str),
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+        labels: labels.map(|l| l.split(',').map(String::from).collect()).unwrap_or_default(),
+        blocked: !blocked_by.is_empty(),
+        blocked_by,
+        task_count: row.get(10)?,
+        tasks_done: row.get(11)?,
+        rank: row.get(12)?,
+        claim: claim_owner.zip(claim_expires_at).map(|(owner, expires_at)| Claim { owner, expires_at }),
+    })
+}
+
+impl Database {
+    pub fn open(path: &std::path::Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        // Several CLI processes may write at once; wait for the lock instead of failing
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
+        Ok(Database { conn, board_id: DEFAULT_BOARD_ID })
     }
 
-    #[allow(dead_code)]
-    pub fn delete_epic(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM epics WHERE id = ?1", [id])?;
-        Ok(())
+    /// Current schema version, as recorded in `PRAGMA user_version`.
+    pub fn schema_version(&self) -> Result<usize> {
+        let version: i64 = self.conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        Ok(version as usize)
     }
 
-    // --- Story CRUD ---
+    /// Versions (1-based) of the migrations not yet applied to this database.
+    pub fn pending_migrations(&self) -> Result<Vec<usize>, StackError> {
+        let current = known_version(self.schema_version()?)?;
+        Ok((current + 1..=MIGRATIONS.len()).collect())
+    }
 
-    pub fn create_story(&self, title: &str, description: &str, epic_id: Option<i64>, status: Status, priority: Priority) -> Result<i64> {
-        self.conn.execute(
-            "INSERT INTO stories (title, description, epic_id, status, priority) VALUES (?1, ?2, ?3, ?4, ?5)",
-            rusqlite::params![title, description, epic_id, status_to_db(&status), priority_to_db(&priority)],
-        )?;
-        Ok(self.conn.last_insert_rowid())
+    /// Apply every pending migration, returning the versions that were run.
+    pub fn migrate(&mut self) -> Result<Vec<usize>, StackError> {
+        self.migrate_to(MIGRATIONS.len())
     }
 
-    pub fn list_stories_by_status(&self, status: Status, epic_id: Option<i64>) -> Result<Vec<Story>> {
-        let (sql, params): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = match epic_id {
-            Some(eid) => (
-                "SELECT id, epic_id, title, description, status, priority, created_at, updated_at FROM stories WHERE status = ?1 AND epic_id = ?2 ORDER BY id",
-                vec![Box::new(status_to_db(&status).to_string()), Box::new(eid)],
-            ),
-            None => (
-                "SELECT id, epic_id, title, description, status, priority, created_at, updated_at FROM stories WHERE status = ?1 ORDER BY id",
-                vec![Box::new(status_to_db(&status).to_string())],
-            ),
-        };
-        let mut stmt = self.conn.prepare(sql)?;
-        let stories = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-            let status_str: String = row.get(4)?;
-            let priority_str: String = row.get(5)?;
-            Ok(Story {
-                id: row.get(0)?,
-                epic_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                status: status_from_db(&status_str),
-                priority: priority_from_db(&priority_str),
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-            })
-        })?.collect::<Result<Vec<_>>>()?;
-        Ok(stories)
+    /// Each step re-reads the version under a write lock, so processes that
+    /// open an outdated database together apply every migration once.
+    fn migrate_to(&mut self, target: usize) -> Result<Vec<usize>, StackError> {
+        let mut applied = Vec::new();
+        if known_version(self.schema_version()?)? >= target {
+            return Ok(applied);
+        }
+        loop {
+            let tx = self.conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            let current: i64 = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+            let version = known_version(current as usize)? + 1;
+            if version > target {
+                break;
+            }
+            tx.execute_batch(MIGRATIONS[version - 1].sql)?;
+            tx.pragma_update(None, "user_version", version as i64)?;
+            tx.commit()?;
+            applied.push(version);
+        }
+        Ok(applied)
     }
 
-    pub fn update_story_status(&self, id: i64, status: Status) -> Result<()> {
-        self.conn.execute(
-            "UPDATE stories SET status = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![status_to_db(&status), id],
-        )?;
-        Ok(())
+    // --- Workflow columns ---
+
+    pub fn list_columns(&self) -> Result<Vec<Column>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM workflow_columns WHERE board_id = ?1 ORDER BY position",
+            COLUMN_COLUMNS,
+        ))?;
+        let columns = stmt.query_map([self.board_id], column_from_row)?.collect::<Result<Vec<_>>>()?;
+        Ok(columns)
     }
 
-    pub fn update_story_title(&self, id: i64, title: &str) -> Result<()> {
-        self.conn.execute(
-            "UPDATE stories SET title = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![title, id],
+    pub fn get_column(&self, key: &Status) -> Result<Column, StackError> {
+        self.board_column(self.board_id, key)
+    }
+
+    fn board_column(&self, board_id: i64, key: &Status) -> Result<Column, StackError> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM workflow_columns WHERE board_id = ?1 AND key = ?2", COLUMN_COLUMNS),
+            rusqlite::params![board_id, key.key()],
+            column_from_row,
+        ).optional()?.ok_or_else(|| StackError::not_found("column", key.key()))
+    }
+
+    /// Add a column at `position` (appended when `None` or out of range).
+    pub fn add_column(&self, key: &Status, name: &str, position: Option<usize>, done: bool, started: bool) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO workflow_columns (board_id, key, name, position, is_done, is_started, sort)
+             VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(position), -1) + 1 FROM workflow_columns WHERE board_id = ?1), ?4, ?5, ?6)",
+            rusqlite::params![self.board_id, key.key(), name, done, started, Sort::default().to_string()],
         )?;
-        Ok(())
+        let id = tx.last_insert_rowid();
+        if let Some(position) = position {
+            reorder_column(&tx, self.board_id, id, Some(position))?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn update_column(
+        &self,
+        key: &Status,
+        name: Option<&str>,
+        done: Option<bool>,
+        started: Option<bool>,
+        sort: Option<Sort>,
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = self.column_id(key)?;
+        if let Some(name) = name {
+            tx.execute("UPDATE workflow_columns SET name = ?1 WHERE id = ?2", rusqlite::params![name, id])?;
+        }
+        if let Some(done) = done {
+            tx.execute("UPDATE workflow_columns SET is_done = ?1 WHERE id = ?2", rusqlite::params![done, id])?;
+        }
+        if let Some(started) = started {
+            tx.execute("UPDATE workflow_columns SET is_started = ?1 WHERE id = ?2", rusqlite::params![started, id])?;
+        }
+        if let Some(sort) = sort {
+            tx.execute("UPDATE workflow_columns SET sort = ?1 WHERE id = ?2", rusqlite::params![sort.to_string(), id])?;
+        }
+        tx.commit()
     }
 
-    pub fn get_story(&self, id: i64) -> Result<Story> {
+    fn column_id(&self, key: &Status) -> Result<i64> {
         self.conn.query_row(
-            "SELECT id, epic_id, title, description, status, priority, created_at, updated_at FROM stories WHERE id = ?1",
-            [id],
-            |row| {
-                let status_str: String = row.get(4)?;
-                let priority_str: String = row.get(5)?;
-                Ok(Story {
-                    id: row.get(0)?,
-                    epic_id: row.get(1)?,
-                    title: row.get(2)?,
-                    description: row.get(3)?,
-                    status: status_from_db(&status_str),
-                    priority: priority_from_db(&priority_str),
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            },
+            "SELECT id FROM workflow_columns WHERE board_id = ?1 AND key = ?2",
+            rusqlite::params![self.board_id, key.key()],
+            |row| row.get(0),
         )
     }
 
-    pub fn delete_story(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM stories WHERE id = ?1", [id])?;
-        Ok(())
+    /// Change the order column `id`'s cards are shown in on the board,
+    /// returning the order it had.
+    pub fn set_column_sort(&self, id: i64, sort: Sort) -> Result<Sort, StackError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let before = tx.query_row(
+            &format!("SELECT {} FROM workflow_columns WHERE id = ?1", COLUMN_COLUMNS),
+            [id],
+            column_from_row,
+        ).optional()?.ok_or(StackError::not_found("column", id))?;
+        tx.execute("UPDATE workflow_columns SET sort = ?1 WHERE id = ?2", rusqlite::params![sort.to_string(), id])?;
+        tx.commit()?;
+        Ok(before.sort)
     }
 
-    pub fn update_story_description(&self, id: i64, description: &str) -> Result<()> {
-        self.conn.execute(
-            "UPDATE stories SET description = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![description, id],
+    /// Set or clear (`None`) the maximum number of stories a column should hold.
+    pub fn set_wip_limit(&self, key: &Status, limit: Option<u32>) -> Result<(), StackError> {
+        let updated = self.conn.execute(
+            "UPDATE workflow_columns SET wip_limit = ?1 WHERE board_id = ?2 AND key = ?3",
+            rusqlite::params![limit, self.board_id, key.key()],
         )?;
+        if updated == 0 {
+            return Err(StackError::not_found("column", key.key()));
+        }
         Ok(())
     }
 
-    pub fn update_story_priority(&self, id: i64, priority: Priority) -> Result<()> {
-        self.conn.execute(
-            "UPDATE stories SET priority = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![priority_to_db(&priority), id],
-        )?;
-        Ok(())
+    /// Describes the WIP limit that moving story `id` into `status` would
+    /// exceed, or `None` if the move is within limits. The limit is the one on
+    /// the story's own board, which need not be the current one.
+    pub fn wip_violation(&self, id: i64, status: &Status) -> Result<Option<String>, StackError> {
+        let (board_id, already_there): (i64, bool) = self.conn.query_row(
+            "SELECT board_id, status = ?2 FROM stories WHERE id = ?1",
+            rusqlite::params![id, status.key()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?.ok_or(StackError::not_found("story", id))?;
+        let column = self.board_column(board_id, status)?;
+        let Some(limit) = column.wip_limit else {
+            return Ok(None);
+        };
+        let count = column.story_count + if already_there { 0 } else { 1 };
+        if count > limit as i64 {
+            Ok(Some(format!("{} would hold {} stories (WIP limit {})", column.name, count, limit)))
+        } else {
+            Ok(None)
+        }
     }
 
-    pub fn update_story_epic(&self, id: i64, epic_id: Option<i64>) -> Result<()> {
-        self.conn.execute(
-            "UPDATE stories SET epic_id = ?1, updated_at = datetime('now') WHERE id = ?2",
-            rusqlite::params![epic_id, id],
+    /// Move a column to index `to`, renumbering the others.
+    pub fn move_column(&self, key: &Status, to: usize) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = self.column_id(key)?;
+        reorder_column(&tx, self.board_id, id, Some(to))?;
+        tx.commit()
+    }
+
+    /// Remove an empty column. Stories must be moved out first, and the
+    /// workflow always keeps at least one column.
+    pub fn remove_column(&self, key: &Status) -> Result<(), StackError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = self.column_id(key)?;
+        let (stories, columns): (i64, i64) = tx.query_row(
+            "SELECT (SELECT COUNT(*) FROM stories WHERE board_id = ?1 AND status = ?2),
+                (SELECT COUNT(*) FROM workflow_columns WHERE board_id = ?1)",
+            rusqlite::params![self.board_id, key.key()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )?;
+        if stories > 0 {
+            return Err(StackError::Conflict(format!("column '{}' still has {} stories; move them first", key, stories)));
+        }
+        if columns == 1 {
+            return Err(StackError::Conflict("cannot remove the last column".to_string()));
+        }
+        reorder_column(&tx, self.board_id, id, None)?;
+        tx.execute("DELETE FROM workflow_columns WHERE id = ?1", [id])?;
+        tx.commit()?;
         Ok(())
     }
 
-    pub fn list_stories(&self, status: Option<Status>, epic_id: Option<i64>) -> Result<Vec<Story>> {
-        let mut sql = String::from(
-            "SELECT id, epic_id, title, description, status, priority, created_at, updated_at FROM stories WHERE 1=1"
-        );
-        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-        let mut idx = 1;
+    // --- Boards ---
 
-        if let Some(s) = status {
-            sql.push_str(&format!(" AND status = ?{}", idx));
-            params.push(Box::new(status_to_db(&s).to_string()));
-            idx += 1;
-        }
-        if let Some(eid) = epic_id {
-            sql.push_str(&format!(" AND epic_id = ?{}", idx));
-            params.push(Box::new(eid));
-        }
-        sql.push_str(" ORDER BY id");
+    pub fn board_id(&self) -> i64 {
+        self.board_id
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let stories = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-            let status_str: String = row.get(4)?;
-            let priority_str: String = row.get(5)?;
-            Ok(Story {
-                id: row.get(0)?,
-                epic_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                status: status_from_db(&status_str),
-                priority: priority_from_db(&priority_str),
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-            })
-        })?.collect::<Result<Vec<_>>>()?;
-        Ok(stories)
+    /// Scope subsequent board-level queries to another board.
+    pub fn set_board(&mut self, id: i64) {
+        self.board_id = id;
     }
 
-    // --- Task CRUD ---
+    /// Create a board with the default workflow columns.
+    pub fn create_board(&self, name: &str) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("INSERT INTO boards (name) VALUES (?1)", [name])?;
+        let id = tx.last_insert_rowid();
+        for (position, (key, column_name, done, started)) in DEFAULT_COLUMNS.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO workflow_columns (board_id, key, name, position, is_done, is_started, sort) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![id, key, column_name, position as i64, done, started, Sort::default().to_string()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
 
-    pub fn create_task(&self, story_id: i64, title: &str) -> Result<i64> {
-        let max_order: i64 = self.conn.query_row(
-            "SELECT COALESCE(MAX(sort_order), -1) FROM tasks WHERE story_id = ?1",
-            [story_id],
-            |row| row.get(0),
-        )?;
-        self.conn.execute(
-            "INSERT INTO tasks (story_id, title, sort_order) VALUES (?1, ?2, ?3)",
-            rusqlite::params![story_id, title, max_order + 1],
-        )?;
-        Ok(self.conn.last_insert_rowid())
+    pub fn list_boards(&self) -> Result<Vec<Board>> {
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM boards ORDER BY id", BOARD_COLUMNS))?;
+        let boards = stmt.query_map([], board_from_row)?.collect::<Result<Vec<_>>>()?;
+        Ok(boards)
     }
 
-    pub fn list_tasks(&self, story_id: i64) -> Result<Vec<Task>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, story_id, title, done, sort_order FROM tasks WHERE story_id = ?1 ORDER BY sort_order"
-        )?;
-        let tasks = stmt.query_map([story_id], |row| {
-            let done_int: i64 = row.get(3)?;
-            Ok(Task {
-                id: row.get(0)?,
-                story_id: row.get(1)?,
-                title: row.get(2)?,
-                done: done_int != 0,
-                sort_order: row.get(4)?,
-            })
-        })?.collect::<Result<Vec<_>>>()?;
-        Ok(tasks)
+    /// Look a board up by name, or by id when `name_or_id` is numeric.
+    pub fn find_board(&self, name_or_id: &str) -> Result<Board, StackError> {
+        self.board_row(name_or_id)?.ok_or_else(|| StackError::not_found("board", name_or_id))
     }
 
-    pub fn get_task(&self, id: i64) -> Result<Task> {
+    fn board_row(&self, name_or_id: &str) -> Result<Option<Board>> {
         self.conn.query_row(
-            "SELECT id, story_id, title, done, sort_order FROM tasks WHERE id = ?1",
-            [id],
-            |row| {
-                let done_int: i64 = row.get(3)?;
-                Ok(Task {
-                    id: row.get(0)?,
-                    story_id: row.get(1)?,
-                    title: row.get(2)?,
-                    done: done_int != 0,
-                    sort_order: row.get(4)?,
-                })
-            },
-        )
+            &format!("SELECT {} FROM boards WHERE name = ?1 OR CAST(id AS TEXT) = ?1", BOARD_COLUMNS),
+            [name_or_id],
+            board_from_row,
+        ).optional()
     }
 
-    pub fn toggle_task(&self, id: i64) -> Result<Task> {
-        self.conn.execute(
-            "UPDATE tasks SET done = NOT done WHERE id = ?1",
-            [id],
-        )?;
-        self.get_task(id)
+    /// Scope this connection to the named board, or to the board last
+    /// switched to (falling back to the default board) when `None`.
+    pub fn use_board(&mut self, name_or_id: Option<&str>) -> Result<(), StackError> {
+        self.board_id = match name_or_id {
+            Some(name) => self.find_board(name)?.id,
+            None => self.current_board()?.unwrap_or(DEFAULT_BOARD_ID),
+        };
+        Ok(())
     }
 
-    pub fn delete_task(&self, id: i64) -> Result<()> {
-        self.conn.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
-        Ok(())
+    /// The board last switched to, if it still exists.
+    pub fn current_board(&self) -> Result<Option<i64>> {
+        let Some(id) = self.get_setting(CURRENT_BOARD_SETTING)? else {
+            return Ok(None);
+        };
+        Ok(self.board_row(&id)?.map(|b| b.id))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rusqlite::Connection;
+    pub fn set_current_board(&self, id: i64) -> Result<()> {
+        self.set_setting(CURRENT_BOARD_SETTING, &id.to_string())
+    }
 
-    fn test_db() -> Database {
-        let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
-        let mut db = Database { conn };
-        db.migrate().unwrap();
-        db
+    // --- Settings ---
+
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        self.conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+            .optional()
     }
 
-    #[test]
-    fn migrate_creates_tables() {
-        let db = test_db();
-        let count: i32 = db.conn.query_row(
-            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name IN ('epics', 'stories', 'tasks')",
-            [],
-            |row| row.get(0),
-        ).unwrap();
-        assert_eq!(count, 3);
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [key, value],
+        )?;
+        Ok(())
     }
 
-    #[test]
-    fn migrate_is_idempotent() {
-        let mut db = test_db();
-        db.migrate().unwrap();
+    /// Whether moves that exceed a WIP limit are refused rather than warned about.
+    pub fn wip_strict(&self) -> Result<bool> {
+        Ok(self.get_setting(WIP_STRICT_SETTING)?.as_deref() == Some("true"))
     }
 
-    // --- Epic tests ---
+    pub fn set_wip_strict(&self, strict: bool) -> Result<()> {
+        self.set_setting(WIP_STRICT_SETTING, if strict { "true" } else { "false" })
+    }
 
-    #[test]
-    fn create_and_list_epics() {
-        let db = test_db();
-        let id = db.create_epic("Auth System", "Build login/signup", "blue").unwrap();
-        assert!(id > 0);
+    // --- Epic CRUD ---
 
-        let epics = db.list_epics().unwrap();
-        assert_eq!(epics.len(), 1);
-        assert_eq!(epics[0].title, "Auth System");
-        assert_eq!(epics[0].color, "blue");
+    #[allow(dead_code)]
+    pub fn create_epic(&self, title: &str, description: &str, color: &str) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO epics (board_id, title, description, color) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![self.board_id, title, description, color],
+        )?;
+        Ok(self.conn.last_insert_rowid())
     }
 
-    #[test]
-    fn delete_epic() {
-        let db = test_db();
-        let id = db.create_epic("Temp", "", "white").unwrap();
-        db.delete_epic(id).unwrap();
-        let epics = db.list_epics().unwrap();
-        assert_eq!(epics.len(), 0);
+    pub fn list_epics(&self) -> Result<Vec<Epic>> {
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM epics WHERE board_id = ?1 ORDER BY id", EPIC_COLUMNS))?;
+        let epics = stmt.query_map([self.board_id], epic_from_row)?.collect::<Result<Vec<_>>>()?;
+        Ok(epics)
     }
 
-    // --- Story tests ---
+    pub fn get_epic(&self, id: i64) -> Result<Epic, StackError> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM epics WHERE id = ?1", EPIC_COLUMNS),
+            [id],
+            epic_from_row,
+        ).optional()?.ok_or(StackError::not_found("epic", id))
+    }
 
-    #[test]
+    /// Change the given fields of an epic, leaving `None` fields as they are.
+    pub fn update_epic(&self, id: i64, title: Option<&str>, description: Option<&str>, color: Option<&str>) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if let Some(title) = title {
+            tx.execute("UPDATE epics SET title = ?1 WHERE id = ?2", rusqlite::params![title, id])?;
+        }
+        if let Some(description) = description {
+            tx.execute("UPDATE epics SET description = ?1 WHERE id = ?2", rusqlite::params![description, id])?;
+        }
+        if let Some(color) = color {
+            tx.execute("UPDATE epics SET color = ?1 WHERE id = ?2", rusqlite::params![color, id])?;
+        }
+        tx.commit()
+    }
+
+    /// Number of the epic's stories in each column of its board, in workflow order.
+    pub fn epic_status_counts(&self, id: i64) -> Result<Vec<(Status, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT wc.key, COUNT(s.id) FROM workflow_columns wc
+             LEFT JOIN stories s ON s.board_id = wc.board_id AND s.status = wc.key AND s.epic_id = ?1
+             WHERE wc.board_id = (SELECT board_id FROM epics WHERE id = ?1)
+             GROUP BY wc.id ORDER BY wc.position"
+        )?;
+        let counts = stmt.query_map([id], |row| {
+            let key: String = row.get(0)?;
+            Ok((Status::new(&key), row.get(1)?))
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(counts)
+    }
+
+    /// Delete an epic, first moving its stories to `reassign_to` (otherwise
+    /// they are left without an epic). Returns how many stories were moved.
+    pub fn delete_epic(&self, id: i64, reassign_to: Option<i64>) -> Result<usize, StackError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let moved = match reassign_to {
+            Some(target) => tx.execute(
+                "UPDATE stories SET epic_id = ?1, updated_at = datetime('now') WHERE epic_id = ?2",
+                rusqlite::params![target, id],
+            )?,
+            None => 0,
+        };
+        if tx.execute("DELETE FROM epics WHERE id = ?1", [id])? == 0 {
+            return Err(StackError::not_found("epic", id));
+        }
+        tx.commit()?;
+        Ok(moved)
+    }
+
+    /// Capture an epic and the ids of its stories before deleting it.
+    pub fn epic_snapshot(&self, id: i64) -> Result<EpicSnapshot, StackError> {
+        let epic = self.get_epic(id)?;
+        let board_id = self.conn.query_row("SELECT board_id FROM epics WHERE id = ?1", [id], |row| row.get(0))?;
+        let mut stmt = self.conn.prepare("SELECT id FROM stories WHERE epic_id = ?1 ORDER BY id")?;
+        let stories = stmt.query_map([id], |row| row.get(0))?.collect::<Result<Vec<_>>>()?;
+        Ok(EpicSnapshot { board_id, epic, stories })
+    }
+
+    /// Re-insert a deleted epic under its original id and give its stories
+    /// back, except those that have since moved to another epic or been deleted.
+    pub fn restore_epic(&self, snapshot: &EpicSnapshot) -> Result<()> {
+        let epic = &snapshot.epic;
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO epics (id, board_id, title, description, color) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![epic.id, snapshot.board_id, epic.title, epic.description, epic.color],
+        )?;
+        for story in &snapshot.stories {
+            tx.execute(
+                "UPDATE stories SET epic_id = ?1 WHERE id = ?2 AND epic_id IS NULL",
+                rusqlite::params![epic.id, story],
+            )?;
+        }
+        tx.commit()
+    }
+
+    // --- Story CRUD ---
+
+    pub fn create_story(&self, title: &str, description: &str, epic_id: Option<i64>, status: &Status, priority: Priority) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO stories (board_id, title, description, epic_id, status, priority, rank)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, (SELECT COALESCE(MAX(rank), 0) + 1 FROM stories WHERE board_id = ?1))",
+            rusqlite::params![self.board_id, title, description, epic_id, status.key(), priority_to_db(&priority)],
+        )?;
+        let id = tx.last_insert_rowid();
+        tx.execute(
+            "INSERT INTO story_status_history (story_id, from_status, to_status) VALUES (?1, NULL, ?2)",
+            rusqlite::params![id, status.key()],
+        )?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn list_stories_by_status(&self, status: &Status, epic_id: Option<i64>, filter: &Filter, sort: Sort) -> Result<Vec<Story>> {
+        self.list_stories(Some(status), epic_id, None, filter, sort, Page::default())
+    }
+
+    /// The story to pick up next from the `from` column: not blocked or
+    /// claimed, most urgent first, then highest on the board. `None` when
+    /// nothing is ready.
+    pub fn next_story(&self, from: &Status, epic_id: Option<i64>, label: Option<&str>) -> Result<Option<Story>> {
+        let mut sql = format!("SELECT {} FROM stories WHERE board_id = ?1", STORY_COLUMNS);
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(self.board_id)];
+        let mut terms = vec![Term::Status(vec![from.clone()]), Term::Blocked(false)];
+        terms.extend(epic_id.map(|id| Term::Epic(vec![id])));
+        terms.extend(label.map(|name| Term::Label(vec![name.to_string()])));
+        for term in &terms {
+            push_term(&mut sql, &mut params, term);
+        }
+        sql.push_str(&format!(" AND NOT {} ORDER BY {} DESC, rank, id LIMIT 1", LIVE_CLAIM, PRIORITY_RANK));
+        self.conn.query_row(&sql, rusqlite::params_from_iter(params.iter()), story_from_row).optional()
+    }
+
+    /// Pick the `next_story` and move it to `to` in one write transaction, so
+    /// concurrent callers never claim the same story. With a `lease` of
+    /// (owner, seconds) the story is also claimed for that owner.
+    pub fn claim_next_story(
+        &self,
+        from: &Status,
+        epic_id: Option<i64>,
+        label: Option<&str>,
+        to: &Status,
+        lease: Option<(&str, i64)>,
+    ) -> Result<Option<Story>, StackError> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let Some(story) = self.next_story(from, epic_id, label)? else {
+            return Ok(None);
+        };
+        if let Some(violation) = self.wip_violation(story.id, to)?
+            && self.wip_strict()?
+        {
+            return Err(StackError::Conflict(violation));
+        }
+        tx.execute(
+            "UPDATE stories SET status = ?1, updated_at = datetime('now') WHERE id = ?2",
+            rusqlite::params![to.key(), story.id],
+        )?;
+        tx.execute(
+            "INSERT INTO story_status_history (story_id, from_status, to_status) VALUES (?1, ?2, ?3)",
+            rusqlite::params![story.id, from.key(), to.key()],
+        )?;
+        if let Some((owner, ttl_secs)) = lease {
+            self.write_claim(story.id, owner, ttl_secs)?;
+        }
+        tx.commit()?;
+        self.get_story(story.id).map(Some)
+    }
+
+    /// Lease a story to `owner` for `ttl_secs`. Claiming again as the same
+    /// owner extends the lease; a live claim held by anyone else is a conflict.
+    pub fn claim_story(&self, id: i64, owner: &str, ttl_secs: i64) -> Result<Story, StackError> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        self.check_claim(id, owner, false)?;
+        self.write_claim(id, owner, ttl_secs)?;
+        tx.commit()?;
+        self.get_story(id)
+    }
+
+    /// Push back the expiry of a claim `owner` still holds.
+    pub fn renew_claim(&self, id: i64, owner: &str, ttl_secs: i64) -> Result<Story, StackError> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        self.check_claim(id, owner, true)?;
+        self.write_claim(id, owner, ttl_secs)?;
+        tx.commit()?;
+        self.get_story(id)
+    }
+
+    /// Drop `owner`'s claim on a story. Releasing an unclaimed story is a no-op.
+    pub fn release_claim(&self, id: i64, owner: &str) -> Result<Story, StackError> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        self.check_claim(id, owner, false)?;
+        tx.execute("DELETE FROM story_claims WHERE story_id = ?1", [id])?;
+        tx.commit()?;
+        self.get_story(id)
+    }
+
+    /// Fail unless `owner` may change the claim on a story: nobody else holds a
+    /// live claim and, when `held` is set, `owner` does. Expired claims are
+    /// released first.
+    fn check_claim(&self, id: i64, owner: &str, held: bool) -> Result<(), StackError> {
+        if owner.trim().is_empty() {
+            return Err(StackError::Validation("owner cannot be empty".to_string()));
+        }
+        self.conn.execute("DELETE FROM story_claims WHERE expires_at <= datetime('now')", [])?;
+        match self.get_story(id)?.claim {
+            Some(claim) if claim.owner != owner.trim() => Err(StackError::Conflict(format!(
+                "story {} is claimed by {} until {}", id, claim.owner, claim.expires_at,
+            ))),
+            None if held => Err(StackError::Conflict(format!("story {} is not claimed by {}", id, owner.trim()))),
+            _ => Ok(()),
+        }
+    }
+
+    fn write_claim(&self, id: i64, owner: &str, ttl_secs: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO story_claims (story_id, owner, expires_at) VALUES (?1, ?2, datetime('now', ?3))
+             ON CONFLICT (story_id) DO UPDATE SET owner = excluded.owner, expires_at = excluded.expires_at",
+            rusqlite::params![id, owner.trim(), format!("+{} seconds", ttl_secs)],
+        )?;
+        Ok(())
+    }
+
+    /// Apply `patch` to a story in one transaction, validating every field
+    /// first. `updated_at` is bumped once, and a status change is recorded in
+    /// the history. In strict WIP mode, moving into a full column is a conflict;
+    /// the write lock is taken up front so concurrent moves can't both fit.
+    pub fn update_story(&self, id: i64, patch: &StoryPatch) -> Result<Story, StackError> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let (board_id, from_status): (i64, String) = tx.query_row(
+            "SELECT board_id, status FROM stories WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?.ok_or(StackError::not_found("story", id))?;
+
+        let mut sets: Vec<&str> = vec![];
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
+        if let Some(title) = &patch.title {
+            if title.trim().is_empty() {
+                return Err(StackError::Validation("title cannot be empty".to_string()));
+            }
+            sets.push("title = ?");
+            params.push(Box::new(title.trim().to_string()));
+        }
+        if let Some(description) = &patch.description {
+            sets.push("description = ?");
+            params.push(Box::new(description.clone()));
+        }
+        if let Some(status) = &patch.status {
+            let known: bool = tx.query_row(
+                "SELECT EXISTS (SELECT 1 FROM workflow_columns WHERE board_id = ?1 AND key = ?2)",
+                rusqlite::params![board_id, status.key()],
+                |row| row.get(0),
+            )?;
+            if !known {
+                return Err(StackError::Validation(format!("unknown status: '{}'", status)));
+            }
+            if status.key() != from_status
+                && let Some(violation) = self.wip_violation(id, status)?
+                && self.wip_strict()?
+            {
+                return Err(StackError::Conflict(violation));
+            }
+            sets.push("status = ?");
+            params.push(Box::new(status.key().to_string()));
+        }
+        if let Some(priority) = &patch.priority {
+            sets.push("priority = ?");
+            params.push(Box::new(priority_to_db(priority)));
+        }
+        if let Some(epic_id) = patch.epic_id {
+            if let Some(epic_id) = epic_id {
+                let exists: bool = tx.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM epics WHERE id = ?1 AND board_id = ?2)",
+                    rusqlite::params![epic_id, board_id],
+                    |row| row.get(0),
+                )?;
+                if !exists {
+                    return Err(StackError::not_found("epic", epic_id));
+                }
+            }
+            sets.push("epic_id = ?");
+            params.push(Box::new(epic_id));
+        }
+
+        if !sets.is_empty() {
+            let sql = format!("UPDATE stories SET {}, updated_at = datetime('now') WHERE id = ?", sets.join(", "));
+            params.push(Box::new(id));
+            tx.execute(&sql, rusqlite::params_from_iter(params.iter().map(|p| p.as_ref())))?;
+        }
+        if let Some(status) = &patch.status
+            && status.key() != from_status
+        {
+            tx.execute(
+                "INSERT INTO story_status_history (story_id, from_status, to_status) VALUES (?1, ?2, ?3)",
+                rusqlite::params![id, from_status, status.key()],
+            )?;
+        }
+        tx.commit()?;
+        self.get_story(id)
+    }
+
+    /// Move story `id` right before `anchor` on its board, or right after it
+    /// when `after` is set, in one write transaction. Only `id`'s rank changes,
+    /// unless neighbouring ranks have grown too close to fit another between
+    /// them and the board is renumbered first. Returns the `(anchor, after)`
+    /// placement that puts the story back where it was.
+    pub fn rank_story(&self, id: i64, anchor: i64, after: bool) -> Result<(i64, bool), StackError> {
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+        let (board_id, rank) = self.story_rank(id)?;
+        let (anchor_board, _) = self.story_rank(anchor)?;
+        if id == anchor {
+            return Err(StackError::Validation(format!("cannot rank story {} relative to itself", id)));
+        }
+        if board_id != anchor_board {
+            return Err(StackError::Validation(format!("stories {} and {} are on different boards", id, anchor)));
+        }
+        // The anchor shares the board, so the story has a neighbour on some side
+        let previous = match self.neighbour(board_id, id, (rank, id), false)? {
+            Some((before, _)) => (before, true),
+            None => (self.neighbour(board_id, id, (rank, id), true)?.map_or(anchor, |(next, _)| next), false),
+        };
+        let rank = loop {
+            if let Some(rank) = self.rank_beside(board_id, id, anchor, after)? {
+                break rank;
+            }
+            self.renumber_ranks(board_id)?;
+        };
+        tx.execute("UPDATE stories SET rank = ?1 WHERE id = ?2", rusqlite::params![rank, id])?;
+        tx.commit()?;
+        Ok(previous)
+    }
+
+    fn story_rank(&self, id: i64) -> Result<(i64, f64), StackError> {
+        self.conn.query_row(
+            "SELECT board_id, rank FROM stories WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?.ok_or(StackError::not_found("story", id))
+    }
+
+    /// The story nearest to the `(rank, id)` position `at` on one side, as
+    /// `(id, rank)`, ignoring story `skip`.
+    fn neighbour(&self, board_id: i64, skip: i64, at: (f64, i64), after: bool) -> Result<Option<(i64, f64)>> {
+        let (past, direction) = if after { (">", "ASC") } else { ("<", "DESC") };
+        self.conn.query_row(
+            &format!(
+                "SELECT id, rank FROM stories WHERE board_id = ?1 AND id <> ?2 AND (rank, id) {past} (?3, ?4)
+                 ORDER BY rank {direction}, id {direction} LIMIT 1",
+                past = past, direction = direction,
+            ),
+            rusqlite::params![board_id, skip, at.0, at.1],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()
+    }
+
+    /// A rank right before (or `after`) `anchor`, ignoring story `id`. `None`
+    /// when the ranks around the anchor are too close to fit another.
+    fn rank_beside(&self, board_id: i64, id: i64, anchor: i64, after: bool) -> Result<Option<f64>, StackError> {
+        let (_, anchor_rank) = self.story_rank(anchor)?;
+        let Some((_, neighbour)) = self.neighbour(board_id, id, (anchor_rank, anchor), after)? else {
+            return Ok(Some(if after { anchor_rank + 1.0 } else { anchor_rank - 1.0 }));
+        };
+        let rank = (anchor_rank + neighbour) / 2.0;
+        Ok(Some(rank).filter(|rank| *rank != anchor_rank && *rank != neighbour))
+    }
+
+    /// Spread a board's ranks out to 1, 2, 3… keeping their order.
+    fn renumber_ranks(&self, board_id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE stories SET rank = ordered.n FROM (
+                SELECT id, ROW_NUMBER() OVER (ORDER BY rank, id) AS n FROM stories WHERE board_id = ?1
+            ) AS ordered WHERE stories.id = ordered.id",
+            [board_id],
+        )?;
+        Ok(())
+    }
+
+    /// Status transitions of a story, oldest first. Each entry's `duration_secs`
+    /// is how long the story stayed in `to_status` (up to now for the latest).
+    pub fn story_history(&self, story_id: i64) -> Result<Vec<StatusChange>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, story_id, from_status, to_status, changed_at,
+                CAST(ROUND((julianday(COALESCE(LEAD(changed_at) OVER (ORDER BY id), datetime('now')))
+                    - julianday(changed_at)) * 86400) AS INTEGER)
+             FROM story_status_history WHERE story_id = ?1 ORDER BY id"
+        )?;
+        let history = stmt.query_map([story_id], |row| {
+            let from_str: Option<String> = row.get(2)?;
+            let to_str: String = row.get(3)?;
+            Ok(StatusChange {
+                id: row.get(0)?,
+                story_id: row.get(1)?,
+                from_status: from_str.as_deref().map(Status::new),
+                to_status: Status::new(&to_str),
+                changed_at: row.get(4)?,
+                duration_secs: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(history)
+    }
+
+    pub fn get_story(&self, id: i64) -> Result<Story, StackError> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM stories WHERE id = ?1", STORY_COLUMNS),
+            [id],
+            story_from_row,
+        ).optional()?.ok_or(StackError::not_found("story", id))
+    }
+
+    pub fn delete_story(&self, id: i64) -> Result<(), StackError> {
+        if self.conn.execute("DELETE FROM stories WHERE id = ?1", [id])? == 0 {
+            return Err(StackError::not_found("story", id));
+        }
+        Ok(())
+    }
+
+    /// Capture a story and its tasks, history and dependencies before deleting it.
+    pub fn story_snapshot(&self, id: i64) -> Result<StorySnapshot, StackError> {
+        let story = self.get_story(id)?;
+        let board_id = self.conn.query_row("SELECT board_id FROM stories WHERE id = ?1", [id], |row| row.get(0))?;
+        let dependencies = |sql: &str| -> Result<Vec<i64>> {
+            let mut stmt = self.conn.prepare(sql)?;
+            stmt.query_map([id], |row| row.get(0))?.collect()
+        };
+        Ok(StorySnapshot {
+            board_id,
+            tasks: self.list_tasks(id)?,
+            history: self.story_history(id)?,
+            blocks: dependencies("SELECT blocked_id FROM story_dependencies WHERE blocker_id = ?1")?,
+            blockers: dependencies("SELECT blocker_id FROM story_dependencies WHERE blocked_id = ?1")?,
+            story,
+        })
+    }
+
+    /// Re-insert a deleted story under its original id. Dependencies on
+    /// stories that have since been deleted are dropped, as is a deleted epic.
+    pub fn restore_story(&self, snapshot: &StorySnapshot) -> Result<()> {
+        let story = &snapshot.story;
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO stories (id, board_id, epic_id, title, description, status, priority, created_at, updated_at, rank)
+             VALUES (?1, ?2, (SELECT id FROM epics WHERE id = ?3), ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                story.id, snapshot.board_id, story.epic_id, story.title, story.description,
+                story.status.key(), priority_to_db(&story.priority), story.created_at, story.updated_at, story.rank,
+            ],
+        )?;
+        for task in &snapshot.tasks {
+            tx.execute(
+                "INSERT INTO tasks (id, story_id, title, done, sort_order) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![task.id, story.id, task.title, task.done as i64, task.sort_order],
+            )?;
+        }
+        for change in &snapshot.history {
+            tx.execute(
+                "INSERT INTO story_status_history (id, story_id, from_status, to_status, changed_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    change.id, story.id, change.from_status.as_ref().map(Status::key),
+                    change.to_status.key(), change.changed_at,
+                ],
+            )?;
+        }
+        for name in &story.labels {
+            tx.execute("INSERT OR IGNORE INTO labels (name) VALUES (?1)", [name])?;
+            tx.execute(
+                "INSERT OR IGNORE INTO story_labels (story_id, label_id) SELECT ?1, id FROM labels WHERE name = ?2",
+                rusqlite::params![story.id, name],
+            )?;
+        }
+        let dependencies = snapshot.blocks.iter().map(|&other| (story.id, other))
+            .chain(snapshot.blockers.iter().map(|&other| (other, story.id)));
+        for (blocker, blocked) in dependencies {
+            tx.execute(
+                "INSERT OR IGNORE INTO story_dependencies (blocker_id, blocked_id)
+                 SELECT ?1, ?2 WHERE EXISTS (SELECT 1 FROM stories WHERE id = ?1) AND EXISTS (SELECT 1 FROM stories WHERE id = ?2)",
+                rusqlite::params![blocker, blocked],
+            )?;
+        }
+        tx.commit()
+    }
+
+    pub fn list_stories(
+        &self,
+        status: Option<&Status>,
+        epic_id: Option<i64>,
+        label: Option<&str>,
+        filter: &Filter,
+        sort: Sort,
+        page: Page,
+    ) -> Result<Vec<Story>> {
+        let mut sql = format!("SELECT {} FROM stories WHERE board_id = ?1", STORY_COLUMNS);
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(self.board_id)];
+        let mut idx = 2;
+
+        if let Some(s) = status {
+            sql.push_str(&format!(" AND status = ?{}", idx));
+            params.push(Box::new(s.key().to_string()));
+            idx += 1;
+        }
+        if let Some(eid) = epic_id {
+            sql.push_str(&format!(" AND epic_id = ?{}", idx));
+            params.push(Box::new(eid));
+            idx += 1;
+        }
+        if let Some(name) = label {
+            sql.push_str(&format!(
+                " AND id IN (SELECT story_id FROM story_labels JOIN labels ON labels.id = story_labels.label_id WHERE labels.name = ?{})",
+                idx,
+            ));
+            params.push(Box::new(name.to_string()));
+        }
+        for term in &filter.terms {
+            push_term(&mut sql, &mut params, term);
+        }
+
+        let key = match sort.field {
+            SortField::Rank => "rank",
+            SortField::Id => "id",
+            SortField::Priority => PRIORITY_RANK,
+            SortField::Created => "created_at",
+            SortField::Updated => "updated_at",
+            SortField::Title => "title COLLATE NOCASE",
+        };
+        let (direction, past) = if sort.descending { ("DESC", "<") } else { ("ASC", ">") };
+        if let Some(after) = page.after {
+            params.push(Box::new(after));
+            sql.push_str(&format!(
+                " AND ({key}, id) {past} ((SELECT {key} FROM stories WHERE id = ?{n}), ?{n})",
+                key = key, past = past, n = params.len(),
+            ));
+        }
+        sql.push_str(&format!(" ORDER BY {key} {direction}, id {direction}", key = key, direction = direction));
+        // SQLite only accepts OFFSET after a LIMIT; -1 means no limit
+        sql.push_str(&format!(" LIMIT {} OFFSET {}", page.limit.map_or(-1, |l| l as i64), page.offset));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let stories = stmt.query_map(rusqlite::params_from_iter(params.iter()), story_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(stories)
+    }
+
+    /// Full-text search over story titles, descriptions and task titles,
+    /// best match first. Every whitespace-separated term must match as a prefix.
+    pub fn search_stories(&self, query: &str) -> Result<Vec<SearchHit>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(vec![]);
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, hits.snippet, hits.rank AS relevance FROM (
+                SELECT rowid, snippet(story_search, -1, '[', ']', '…', 12) AS snippet, rank
+                FROM story_search WHERE story_search MATCH ?1
+            ) hits JOIN stories ON stories.id = hits.rowid
+            WHERE stories.board_id = ?2
+            ORDER BY hits.rank",
+            STORY_COLUMNS,
+        ))?;
+        let hits = stmt.query_map(rusqlite::params![fts_query, self.board_id], |row| {
+            Ok(SearchHit {
+                story: story_from_row(row)?,
+                snippet: row.get("snippet")?,
+                rank: row.get("relevance")?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(hits)
+    }
+
+    // --- Dependencies ---
+
+    /// Record that `blocker_id` must be done before `blocked_id` can start.
+    /// Fails if `blocked_id` already (transitively) blocks `blocker_id`.
+    pub fn link_stories(&self, blocker_id: i64, blocked_id: i64) -> Result<(), StackError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let creates_cycle: bool = tx.query_row(
+            "WITH RECURSIVE downstream(id) AS (
+                SELECT ?1
+                UNION
+                SELECT dep.blocked_id FROM story_dependencies dep JOIN downstream ON dep.blocker_id = downstream.id
+            )
+            SELECT EXISTS (SELECT 1 FROM downstream WHERE id = ?2)",
+            rusqlite::params![blocked_id, blocker_id],
+            |row| row.get(0),
+        )?;
+        if creates_cycle {
+            return Err(StackError::Conflict(format!(
+                "story {} cannot block story {}: that would create a dependency cycle",
+                blocker_id, blocked_id,
+            )));
+        }
+        tx.execute(
+            "INSERT OR IGNORE INTO story_dependencies (blocker_id, blocked_id) VALUES (?1, ?2)",
+            rusqlite::params![blocker_id, blocked_id],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove a dependency, returning whether one existed.
+    pub fn unlink_stories(&self, blocker_id: i64, blocked_id: i64) -> Result<bool> {
+        let removed = self.conn.execute(
+            "DELETE FROM story_dependencies WHERE blocker_id = ?1 AND blocked_id = ?2",
+            rusqlite::params![blocker_id, blocked_id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    // --- Labels ---
+
+    /// Attach a label to a story, creating the label on first use.
+    pub fn add_label(&self, story_id: i64, name: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("INSERT OR IGNORE INTO labels (name) VALUES (?1)", [name])?;
+        let added = tx.execute(
+            "INSERT OR IGNORE INTO story_labels (story_id, label_id) SELECT ?1, id FROM labels WHERE name = ?2",
+            rusqlite::params![story_id, name],
+        )?;
+        if added > 0 {
+            tx.execute("UPDATE stories SET updated_at = datetime('now') WHERE id = ?1", [story_id])?;
+        }
+        tx.commit()
+    }
+
+    /// Detach a label from a story; labels no story uses any more are dropped.
+    pub fn remove_label(&self, story_id: i64, name: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        let removed = tx.execute(
+            "DELETE FROM story_labels WHERE story_id = ?1 AND label_id = (SELECT id FROM labels WHERE name = ?2)",
+            rusqlite::params![story_id, name],
+        )?;
+        if removed > 0 {
+            tx.execute("UPDATE stories SET updated_at = datetime('now') WHERE id = ?1", [story_id])?;
+        }
+        tx.execute("DELETE FROM labels WHERE id NOT IN (SELECT label_id FROM story_labels)", [])?;
+        tx.commit()
+    }
+
+    pub fn list_labels(&self) -> Result<Vec<Label>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT labels.id, labels.name, COUNT(story_labels.story_id) FROM labels
+             LEFT JOIN story_labels ON story_labels.label_id = labels.id
+             GROUP BY labels.id ORDER BY labels.name"
+        )?;
+        let labels = stmt.query_map([], |row| {
+            Ok(Label {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                story_count: row.get(2)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(labels)
+    }
+
+    // --- Task CRUD ---
+
+    pub fn create_task(&self, story_id: i64, title: &str) -> Result<i64, StackError> {
+        let max_order: Option<i64> = self.conn.query_row(
+            "SELECT (SELECT COALESCE(MAX(sort_order), -1) FROM tasks WHERE story_id = ?1) FROM stories WHERE id = ?1",
+            [story_id],
+            |row| row.get(0),
+        ).optional()?;
+        let max_order = max_order.ok_or(StackError::not_found("story", story_id))?;
+        self.conn.execute(
+            "INSERT INTO tasks (story_id, title, sort_order) VALUES (?1, ?2, ?3)",
+            rusqlite::params![story_id, title, max_order + 1],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn list_tasks(&self, story_id: i64) -> Result<Vec<Task>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, story_id, title, done, sort_order FROM tasks WHERE story_id = ?1 ORDER BY sort_order"
+        )?;
+        let tasks = stmt.query_map([story_id], |row| {
+            let done_int: i64 = row.get(3)?;
+            Ok(Task {
+                id: row.get(0)?,
+                story_id: row.get(1)?,
+                title: row.get(2)?,
+                done: done_int != 0,
+                sort_order: row.get(4)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
+        Ok(tasks)
+    }
+
+    pub fn get_task(&self, id: i64) -> Result<Task, StackError> {
+        self.conn.query_row(
+            "SELECT id, story_id, title, done, sort_order FROM tasks WHERE id = ?1",
+            [id],
+            |row| {
+                let done_int: i64 = row.get(3)?;
+                Ok(Task {
+                    id: row.get(0)?,
+                    story_id: row.get(1)?,
+                    title: row.get(2)?,
+                    done: done_int != 0,
+                    sort_order: row.get(4)?,
+                })
+            },
+        ).optional()?.ok_or(StackError::not_found("task", id))
+    }
+
+    pub fn toggle_task(&self, id: i64) -> Result<Task, StackError> {
+        self.conn.execute(
+            "UPDATE tasks SET done = NOT done WHERE id = ?1",
+            [id],
+        )?;
+        self.get_task(id)
+    }
+
+    pub fn rename_task(&self, id: i64, title: &str) -> Result<Task, StackError> {
+        if title.trim().is_empty() {
+            return Err(StackError::Validation("title cannot be empty".to_string()));
+        }
+        let updated = self.conn.execute(
+            "UPDATE tasks SET title = ?1 WHERE id = ?2",
+            rusqlite::params![title.trim(), id],
+        )?;
+        if updated == 0 {
+            return Err(StackError::not_found("task", id));
+        }
+        self.get_task(id)
+    }
+
+    /// Mark a task done or not done; unlike `toggle_task`, safe to repeat.
+    pub fn set_task_done(&self, id: i64, done: bool) -> Result<Task, StackError> {
+        self.conn.execute(
+            "UPDATE tasks SET done = ?1 WHERE id = ?2",
+            rusqlite::params![done as i64, id],
+        )?;
+        self.get_task(id)
+    }
+
+    /// Place task `id` at index `to` (clamped) among its story's tasks and
+    /// renumber their sort order.
+    pub fn move_task(&self, id: i64, to: usize) -> Result<Task, StackError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let story_id: i64 = tx.query_row("SELECT story_id FROM tasks WHERE id = ?1", [id], |row| row.get(0))
+            .optional()?.ok_or(StackError::not_found("task", id))?;
+        let mut ids: Vec<i64> = tx.prepare("SELECT id FROM tasks WHERE story_id = ?1 AND id <> ?2 ORDER BY sort_order, id")?
+            .query_map([story_id, id], |row| row.get(0))?
+            .collect::<Result<Vec<_>>>()?;
+        ids.insert(to.min(ids.len()), id);
+        for (sort_order, id) in ids.iter().enumerate() {
+            tx.execute("UPDATE tasks SET sort_order = ?1 WHERE id = ?2", rusqlite::params![sort_order as i64, id])?;
+        }
+        tx.commit()?;
+        self.get_task(id)
+    }
+
+    /// Exchange the positions of two tasks of the same story.
+    pub fn swap_tasks(&self, a: i64, b: i64) -> Result<(), StackError> {
+        let tx = self.conn.unchecked_transaction()?;
+        let order_of = |id: i64| -> Result<i64, StackError> {
+            tx.query_row("SELECT sort_order FROM tasks WHERE id = ?1", [id], |row| row.get(0))
+                .optional()?.ok_or(StackError::not_found("task", id))
+        };
+        let (order_a, order_b) = (order_of(a)?, order_of(b)?);
+        tx.execute("UPDATE tasks SET sort_order = ?1 WHERE id = ?2", rusqlite::params![order_b, a])?;
+        tx.execute("UPDATE tasks SET sort_order = ?1 WHERE id = ?2", rusqlite::params![order_a, b])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn delete_task(&self, id: i64) -> Result<(), StackError> {
+        if self.conn.execute("DELETE FROM tasks WHERE id = ?1", [id])? == 0 {
+            return Err(StackError::not_found("task", id));
+        }
+        Ok(())
+    }
+
+    /// Re-insert a deleted task under its original id and position.
+    pub fn restore_task(&self, task: &Task) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO tasks (id, story_id, title, done, sort_order) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![task.id, task.story_id, task.title, task.done as i64, task.sort_order],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    use crate::error::Key;
+
+    fn set_status(db: &Database, id: i64, status: &Status) -> Result<Story, StackError> {
+        db.update_story(id, &StoryPatch { status: Some(status.clone()), ..Default::default() })
+    }
+
+    fn test_db() -> Database {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        let mut db = Database { conn, board_id: DEFAULT_BOARD_ID };
+        db.migrate().unwrap();
+        db
+    }
+
+    #[test]
+    fn migrate_creates_tables() {
+        let db = test_db();
+        let count: i32 = db.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name IN ('epics', 'stories', 'tasks')",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn migrate_is_idempotent() {
+        let mut db = test_db();
+        assert!(db.migrate().unwrap().is_empty());
+    }
+
+    #[test]
+    fn migrate_records_schema_version() {
+        let db = test_db();
+        assert_eq!(db.schema_version().unwrap(), MIGRATIONS.len());
+        assert!(db.pending_migrations().unwrap().is_empty());
+    }
+
+    #[test]
+    fn refuses_database_from_newer_release() {
+        let mut db = test_db();
+        db.conn.pragma_update(None, "user_version", MIGRATIONS.len() as i64 + 1).unwrap();
+        assert!(matches!(db.migrate(), Err(StackError::Conflict(_))));
+        assert!(db.pending_migrations().is_err());
+    }
+
+    #[test]
+    fn concurrent_opens_apply_each_migration_once() {
+        let path = std::env::temp_dir().join(format!("stack-migrate-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut applied: Vec<usize> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| {
+                Database::open(&path).unwrap().migrate().unwrap()
+            })).collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+        applied.sort();
+        assert_eq!(applied, (1..=MIGRATIONS.len()).collect::<Vec<_>>());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // --- Migration fixtures ---
+
+    /// Schema written by releases before `user_version` was tracked.
+    const UNVERSIONED_SCHEMA: &str = "
+        CREATE TABLE epics (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            color TEXT NOT NULL DEFAULT 'white'
+        );
+        CREATE TABLE stories (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            epic_id INTEGER REFERENCES epics(id) ON DELETE SET NULL,
+            title TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            status TEXT NOT NULL DEFAULT 'todo',
+            priority TEXT NOT NULL DEFAULT 'medium',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            story_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+            title TEXT NOT NULL,
+            done INTEGER NOT NULL DEFAULT 0,
+            sort_order INTEGER NOT NULL DEFAULT 0
+        );
+        INSERT INTO epics (title, color) VALUES ('Legacy Epic', 'blue');
+        INSERT INTO stories (epic_id, title, status, priority) VALUES (1, 'Legacy Story', 'in_progress', 'high');
+        INSERT INTO tasks (story_id, title, done) VALUES (1, 'Legacy Task', 1);
+        INSERT INTO stories (title, status) VALUES ('Archived Story', 'archived');
+    ";
+
+    fn db_at_version(version: usize) -> Database {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys=ON;").unwrap();
+        let mut db = Database { conn, board_id: DEFAULT_BOARD_ID };
+        db.migrate_to(version).unwrap();
+        db
+    }
+
+    #[test]
+    fn upgrades_unversioned_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(UNVERSIONED_SCHEMA).unwrap();
+        let mut db = Database { conn, board_id: DEFAULT_BOARD_ID };
+        assert_eq!(db.schema_version().unwrap(), 0);
+
+        let applied = db.migrate().unwrap();
+        assert_eq!(applied, (1..=MIGRATIONS.len()).collect::<Vec<_>>());
+        assert_eq!(db.schema_version().unwrap(), MIGRATIONS.len());
+
+        let story = db.get_story(1).unwrap();
+        assert_eq!(story.title, "Legacy Story");
+        assert_eq!(story.status, Status::new("in_progress"));
+        assert_eq!(story.epic_id, Some(1));
+        assert_eq!(db.list_epics().unwrap()[0].color, "blue");
+        assert!(db.list_tasks(1).unwrap()[0].done);
+
+        let history = db.story_history(1).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].to_status, Status::new("in_progress"));
+
+        let hits = db.search_stories("legacy task").unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].story.id, 1);
+
+        assert_eq!(db.list_columns().unwrap().len(), 4);
+        assert_eq!(db.get_story(2).unwrap().status, Status::new("todo"));
+        assert_eq!(db.list_boards().unwrap()[0].story_count, 2);
+    }
+
+    #[test]
+    fn upgrades_from_every_older_version() {
+        for version in 0..MIGRATIONS.len() {
+            let mut db = db_at_version(version);
+            assert_eq!(db.schema_version().unwrap(), version);
+            let applied = db.migrate().unwrap();
+            assert_eq!(applied, (version + 1..=MIGRATIONS.len()).collect::<Vec<_>>());
+
+            let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+            db.create_task(sid, "T1").unwrap();
+            assert_eq!(db.get_story(sid).unwrap().title, "S1");
+        }
+    }
+
+    #[test]
+    fn pending_migrations_lists_unapplied_versions() {
+        let db = db_at_version(0);
+        assert_eq!(db.pending_migrations().unwrap(), (1..=MIGRATIONS.len()).collect::<Vec<_>>());
+    }
+
+    // --- Workflow column tests ---
+
+    fn column_keys(db: &Database) -> Vec<String> {
+        db.list_columns().unwrap().iter().map(|c| c.key.key().to_string()).collect()
+    }
+
+    #[test]
+    fn default_workflow_has_four_columns() {
+        let db = test_db();
+        let columns = db.list_columns().unwrap();
+        assert_eq!(column_keys(&db), vec!["todo", "in_progress", "in_review", "done"]);
+        assert_eq!(columns[1].name, "In Progress");
+        assert_eq!(columns.iter().filter(|c| c.done).count(), 1);
+        assert!(columns[3].done);
+    }
+
+    #[test]
+    fn add_move_and_remove_columns() {
+        let db = test_db();
+        db.add_column(&Status::new("backlog"), "Backlog", Some(0), false, false).unwrap();
+        db.add_column(&Status::new("qa"), "QA", None, false, true).unwrap();
+        assert_eq!(column_keys(&db), vec!["backlog", "todo", "in_progress", "in_review", "done", "qa"]);
+
+        db.move_column(&Status::new("qa"), 4).unwrap();
+        assert_eq!(column_keys(&db), vec!["backlog", "todo", "in_progress", "in_review", "qa", "done"]);
+        let positions: Vec<i64> = db.list_columns().unwrap().iter().map(|c| c.position).collect();
+        assert_eq!(positions, vec![0, 1, 2, 3, 4, 5]);
+
+        db.update_column(&Status::new("qa"), Some("Quality"), Some(false), None, None).unwrap();
+        assert_eq!(db.get_column(&Status::new("qa")).unwrap().name, "Quality");
+
+        db.remove_column(&Status::new("backlog")).unwrap();
+        assert_eq!(column_keys(&db), vec!["todo", "in_progress", "in_review", "qa", "done"]);
+        assert!(db.add_column(&Status::new("qa"), "Dup", None, false, false).is_err());
+    }
+
+    #[test]
+    fn remove_column_refuses_when_stories_remain() {
+        let db = test_db();
+        db.create_story("S1", "", None, &Status::new("in_review"), Priority::Medium).unwrap();
+        assert!(db.remove_column(&Status::new("in_review")).is_err());
+        assert_eq!(db.list_columns().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn stories_require_an_existing_column() {
+        let db = test_db();
+        assert!(db.create_story("S1", "", None, &Status::new("qa"), Priority::Medium).is_err());
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        assert!(set_status(&db, sid, &Status::new("qa")).is_err());
+
+        db.add_column(&Status::new("qa"), "QA", None, false, true).unwrap();
+        set_status(&db, sid, &Status::new("qa")).unwrap();
+        assert_eq!(db.get_story(sid).unwrap().status, Status::new("qa"));
+    }
+
+    #[test]
+    fn done_flag_controls_blocking() {
+        let db = test_db();
+        db.add_column(&Status::new("shipped"), "Shipped", None, true, false).unwrap();
+        let a = db.create_story("A", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let b = db.create_story("B", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        db.link_stories(a, b).unwrap();
+        set_status(&db, a, &Status::new("shipped")).unwrap();
+        assert!(!db.get_story(b).unwrap().blocked);
+    }
+
+    #[test]
+    fn wip_violation_counts_the_incoming_story() {
+        let db = test_db();
+        let in_progress = Status::new("in_progress");
+        db.set_wip_limit(&in_progress, Some(1)).unwrap();
+        let a = db.create_story("A", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let b = db.create_story("B", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+
+        assert_eq!(db.wip_violation(a, &in_progress).unwrap(), None);
+        set_status(&db, a, &in_progress).unwrap();
+        assert_eq!(db.wip_violation(a, &in_progress).unwrap(), None);
+        let msg = db.wip_violation(b, &in_progress).unwrap().unwrap();
+        assert!(msg.contains("In Progress would hold 2 stories"));
+
+        db.set_wip_limit(&in_progress, None).unwrap();
+        assert_eq!(db.wip_violation(b, &in_progress).unwrap(), None);
+        assert!(matches!(db.set_wip_limit(&Status::new("nope"), Some(1)), Err(StackError::NotFound { entity: "column", .. })));
+    }
+
+    #[test]
+    fn columns_report_limits_and_counts() {
+        let db = test_db();
+        db.set_wip_limit(&Status::new("todo"), Some(3)).unwrap();
+        db.create_story("A", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let todo = db.get_column(&Status::new("todo")).unwrap();
+        assert_eq!(todo.wip_limit, Some(3));
+        assert_eq!(todo.story_count, 1);
+    }
+
+    #[test]
+    fn strict_wip_limit_refuses_moves_into_full_column() {
+        let db = test_db();
+        let (todo, in_progress) = (Status::new("todo"), Status::new("in_progress"));
+        let a = db.create_story("A", "", None, &todo, Priority::Medium).unwrap();
+        let b = db.create_story("B", "", None, &todo, Priority::Medium).unwrap();
+        db.set_wip_limit(&in_progress, Some(1)).unwrap();
+        set_status(&db, a, &in_progress).unwrap();
+
+        db.set_wip_strict(true).unwrap();
+        assert!(matches!(set_status(&db, b, &in_progress), Err(StackError::Conflict(_))));
+        assert_eq!(db.get_story(b).unwrap().status, todo);
+        db.update_story(a, &StoryPatch { title: Some("A2".to_string()), ..Default::default() }).unwrap();
+        // Without strict mode the move goes through and callers only warn
+        db.set_wip_strict(false).unwrap();
+        set_status(&db, b, &in_progress).unwrap();
+    }
+
+    #[test]
+    fn wip_limit_comes_from_the_stories_own_board() {
+        let mut db = test_db();
+        let (todo, in_progress) = (Status::new("todo"), Status::new("in_progress"));
+        db.create_board("Side").unwrap();
+        db.use_board(Some("Side")).unwrap();
+        let busy = db.create_story("Busy", "", None, &in_progress, Priority::Medium).unwrap();
+        let side = db.create_story("Side", "", None, &todo, Priority::Medium).unwrap();
+        db.set_wip_limit(&in_progress, Some(1)).unwrap();
+        db.set_wip_strict(true).unwrap();
+
+        // The default board's In Progress is empty and unlimited, but the story lives on Side
+        db.use_board(None).unwrap();
+        let main = db.create_story("Main", "", None, &todo, Priority::Medium).unwrap();
+        assert!(db.wip_violation(side, &in_progress).unwrap().is_some());
+        assert!(matches!(set_status(&db, side, &in_progress), Err(StackError::Conflict(_))));
+
+        db.use_board(Some("Side")).unwrap();
+        assert_eq!(db.wip_violation(main, &in_progress).unwrap(), None);
+        set_status(&db, main, &in_progress).unwrap();
+        set_status(&db, busy, &todo).unwrap();
+        set_status(&db, side, &in_progress).unwrap();
+    }
+
+    #[test]
+    fn wip_strict_setting_round_trips() {
+        let db = test_db();
+        assert!(!db.wip_strict().unwrap());
+        db.set_wip_strict(true).unwrap();
+        assert!(db.wip_strict().unwrap());
+        db.set_wip_strict(false).unwrap();
+        assert!(!db.wip_strict().unwrap());
+    }
+
+    // --- Board tests ---
+
+    #[test]
+    fn boards_scope_stories_epics_and_columns() {
+        let mut db = test_db();
+        let home_story = db.create_story("Home", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        db.create_epic("Home Epic", "", "white").unwrap();
+        db.add_column(&Status::new("qa"), "QA", None, false, true).unwrap();
+
+        let work = db.create_board("Work").unwrap();
+        db.set_board(work);
+        assert_eq!(column_keys(&db), vec!["todo", "in_progress", "in_review", "done"]);
+        assert!(db.list_epics().unwrap().is_empty());
+        assert!(db.list_stories(None, None, None, &Filter::default(), Sort::default(), Page::default()).unwrap().is_empty());
+        assert!(db.create_story("W", "", None, &Status::new("qa"), Priority::Medium).is_err());
+
+        let work_story = db.create_story("Work item", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        assert_eq!(db.list_stories(None, None, None, &Filter::default(), Sort::default(), Page::default()).unwrap().len(), 1);
+        assert_eq!(db.search_stories("work").unwrap()[0].story.id, work_story);
+        assert!(db.search_stories("home").unwrap().is_empty());
+
+        db.set_board(DEFAULT_BOARD_ID);
+        let stories = db.list_stories(None, None, None, &Filter::default(), Sort::default(), Page::default()).unwrap();
+        assert_eq!(stories.iter().map(|s| s.id).collect::<Vec<_>>(), vec![home_story]);
+        assert_eq!(db.get_column(&Status::new("todo")).unwrap().story_count, 1);
+    }
+
+    #[test]
+    fn find_board_by_name_or_id() {
+        let db = test_db();
+        let id = db.create_board("Chores").unwrap();
+        assert_eq!(db.find_board("chores").unwrap().id, id);
+        assert_eq!(db.find_board(&id.to_string()).unwrap().name, "Chores");
+        assert!(db.find_board("missing").is_err());
+        assert!(db.create_board("CHORES").is_err());
+
+        let boards = db.list_boards().unwrap();
+        assert_eq!(boards.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), vec!["default", "Chores"]);
+    }
+
+    #[test]
+    fn current_board_is_remembered() {
+        let mut db = test_db();
+        assert_eq!(db.current_board().unwrap(), None);
+        let id = db.create_board("Side Project").unwrap();
+        db.set_current_board(id).unwrap();
+        assert_eq!(db.current_board().unwrap(), Some(id));
+
+        db.use_board(None).unwrap();
+        assert_eq!(db.board_id(), id);
+        db.use_board(Some("default")).unwrap();
+        assert_eq!(db.board_id(), DEFAULT_BOARD_ID);
+        assert!(db.use_board(Some("nope")).is_err());
+    }
+
+    // --- Epic tests ---
+
+    #[test]
+    fn create_and_list_epics() {
+        let db = test_db();
+        let id = db.create_epic("Auth System", "Build login/signup", "blue").unwrap();
+        assert!(id > 0);
+
+        let epics = db.list_epics().unwrap();
+        assert_eq!(epics.len(), 1);
+        assert_eq!(epics[0].title, "Auth System");
+        assert_eq!(epics[0].color, "blue");
+    }
+
+    #[test]
+    fn delete_epic() {
+        let db = test_db();
+        let id = db.create_epic("Temp", "", "white").unwrap();
+        db.delete_epic(id, None).unwrap();
+        let epics = db.list_epics().unwrap();
+        assert_eq!(epics.len(), 0);
+    }
+
+    #[test]
+    fn delete_epic_reassigns_stories() {
+        let db = test_db();
+        let old = db.create_epic("Old", "", "white").unwrap();
+        let new = db.create_epic("New", "", "white").unwrap();
+        let sid = db.create_story("S1", "", Some(old), &Status::new("todo"), Priority::Medium).unwrap();
+        db.create_story("S2", "", Some(new), &Status::new("todo"), Priority::Medium).unwrap();
+
+        assert_eq!(db.delete_epic(old, Some(new)).unwrap(), 1);
+        assert_eq!(db.get_story(sid).unwrap().epic_id, Some(new));
+        assert_eq!(db.get_epic(new).unwrap().story_count, 2);
+
+        db.delete_epic(new, None).unwrap();
+        assert_eq!(db.get_story(sid).unwrap().epic_id, None);
+    }
+
+    #[test]
+    fn update_epic_and_progress() {
+        let db = test_db();
+        let id = db.create_epic("Temp", "", "white").unwrap();
+        db.update_epic(id, Some("Launch"), None, Some("green")).unwrap();
+        db.create_story("A", "", Some(id), &Status::new("todo"), Priority::Medium).unwrap();
+        db.create_story("B", "", Some(id), &Status::new("done"), Priority::Medium).unwrap();
+        db.create_story("C", "", None, &Status::new("done"), Priority::Medium).unwrap();
+
+        let epic = db.get_epic(id).unwrap();
+        assert_eq!((epic.title.as_str(), epic.color.as_str()), ("Launch", "green"));
+        assert_eq!((epic.done_count, epic.story_count), (1, 2));
+        assert_eq!(epic.percent_complete(), 50);
+
+        let counts: Vec<(String, i64)> = db.epic_status_counts(id).unwrap()
+            .into_iter().map(|(s, n)| (s.key().to_string(), n)).collect();
+        assert_eq!(counts, vec![
+            ("todo".to_string(), 1), ("in_progress".to_string(), 0),
+            ("in_review".to_string(), 0), ("done".to_string(), 1),
+        ]);
+    }
+
+    // --- Story tests ---
+
+    #[test]
     fn create_and_list_stories_by_status() {
         let db = test_db();
         let eid = db.create_epic("E1", "", "white").unwrap();
-        db.create_story("Story A", "", Some(eid), Status::ToDo, Priority::High).unwrap();
-        db.create_story("Story B", "", Some(eid), Status::InProgress, Priority::Low).unwrap();
-        db.create_story("Story C", "", None, Status::ToDo, Priority::Medium).unwrap();
+        db.create_story("Story A", "", Some(eid), &Status::new("todo"), Priority::High).unwrap();
+        db.create_story("Story B", "", Some(eid), &Status::new("in_progress"), Priority::Low).unwrap();
+        db.create_story("Story C", "", None, &Status::new("todo"), Priority::Medium).unwrap();
 
-        let todo = db.list_stories_by_status(Status::ToDo, None).unwrap();
+        let todo = db.list_stories_by_status(&Status::new("todo"), None, &Filter::default(), Sort::default()).unwrap();
         assert_eq!(todo.len(), 2);
 
-        let filtered = db.list_stories_by_status(Status::ToDo, Some(eid)).unwrap();
+        let filtered = db.list_stories_by_status(&Status::new("todo"), Some(eid), &Filter::default(), Sort::default()).unwrap();
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].title, "Story A");
     }
@@ -388,29 +1971,490 @@ mod tests {
     #[test]
     fn update_story_status() {
         let db = test_db();
-        let sid = db.create_story("S1", "", None, Status::ToDo, Priority::Medium).unwrap();
-        db.update_story_status(sid, Status::InProgress).unwrap();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        set_status(&db, sid, &Status::new("in_progress")).unwrap();
 
-        let stories = db.list_stories_by_status(Status::InProgress, None).unwrap();
+        let stories = db.list_stories_by_status(&Status::new("in_progress"), None, &Filter::default(), Sort::default()).unwrap();
         assert_eq!(stories.len(), 1);
         assert_eq!(stories[0].id, sid);
     }
 
+    #[test]
+    fn status_changes_are_recorded_in_history() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        set_status(&db, sid, &Status::new("in_progress")).unwrap();
+        set_status(&db, sid, &Status::new("in_progress")).unwrap();
+        set_status(&db, sid, &Status::new("in_review")).unwrap();
+
+        let history = db.story_history(sid).unwrap();
+        let transitions: Vec<_> = history.iter().map(|h| (h.from_status.clone(), h.to_status.clone())).collect();
+        assert_eq!(transitions, vec![
+            (None, Status::new("todo")),
+            (Some(Status::new("todo")), Status::new("in_progress")),
+            (Some(Status::new("in_progress")), Status::new("in_review")),
+        ]);
+        assert!(history.iter().all(|h| h.duration_secs >= 0));
+    }
+
+    #[test]
+    fn history_is_removed_with_story() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        set_status(&db, sid, &Status::new("done")).unwrap();
+        db.delete_story(sid).unwrap();
+        assert!(db.story_history(sid).unwrap().is_empty());
+    }
+
+    // --- Dependency tests ---
+
+    #[test]
+    fn story_is_blocked_until_blockers_are_done() {
+        let db = test_db();
+        let a = db.create_story("A", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let b = db.create_story("B", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        db.link_stories(a, b).unwrap();
+
+        let story = db.get_story(b).unwrap();
+        assert!(story.blocked);
+        assert_eq!(story.blocked_by, vec![a]);
+        assert!(!db.get_story(a).unwrap().blocked);
+
+        set_status(&db, a, &Status::new("done")).unwrap();
+        assert!(!db.get_story(b).unwrap().blocked);
+    }
+
+    #[test]
+    fn link_rejects_cycles() {
+        let db = test_db();
+        let a = db.create_story("A", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let b = db.create_story("B", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let c = db.create_story("C", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        db.link_stories(a, b).unwrap();
+        db.link_stories(b, c).unwrap();
+
+        assert!(db.link_stories(c, a).is_err());
+        assert!(db.link_stories(b, a).is_err());
+        assert!(db.link_stories(a, a).is_err());
+        // Redundant but acyclic edges are fine
+        db.link_stories(a, c).unwrap();
+    }
+
+    #[test]
+    fn unlink_removes_dependency() {
+        let db = test_db();
+        let a = db.create_story("A", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let b = db.create_story("B", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        db.link_stories(a, b).unwrap();
+        assert!(db.unlink_stories(a, b).unwrap());
+        assert!(!db.unlink_stories(a, b).unwrap());
+        assert!(!db.get_story(b).unwrap().blocked);
+    }
+
+    // --- Label tests ---
+
+    #[test]
+    fn add_and_remove_labels() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        db.add_label(sid, "backend").unwrap();
+        db.add_label(sid, "api").unwrap();
+        db.add_label(sid, "API").unwrap();
+        assert_eq!(db.get_story(sid).unwrap().labels, vec!["api", "backend"]);
+
+        db.remove_label(sid, "backend").unwrap();
+        assert_eq!(db.get_story(sid).unwrap().labels, vec!["api"]);
+        let labels = db.list_labels().unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].name, "api");
+        assert_eq!(labels[0].story_count, 1);
+    }
+
+    #[test]
+    fn list_stories_filters_by_label() {
+        let db = test_db();
+        let a = db.create_story("A", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let b = db.create_story("B", "", None, &Status::new("done"), Priority::Medium).unwrap();
+        db.create_story("C", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        db.add_label(a, "bug").unwrap();
+        db.add_label(b, "bug").unwrap();
+
+        let bugs = db.list_stories(None, None, Some("bug"), &Filter::default(), Sort::default(), Page::default()).unwrap();
+        assert_eq!(bugs.iter().map(|s| s.id).collect::<Vec<_>>(), vec![a, b]);
+        let todo_bugs = db.list_stories(Some(&Status::new("todo")), None, Some("bug"), &Filter::default(), Sort::default(), Page::default()).unwrap();
+        assert_eq!(todo_bugs.len(), 1);
+        assert!(db.list_stories(None, None, Some("missing"), &Filter::default(), Sort::default(), Page::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn list_stories_sorts_and_pages() {
+        let db = test_db();
+        let todo = Status::new("todo");
+        let b = db.create_story("banana", "", None, &todo, Priority::High).unwrap();
+        let a = db.create_story("Apple", "", None, &todo, Priority::Low).unwrap();
+        let c = db.create_story("cherry", "", None, &todo, Priority::High).unwrap();
+        let ids = |sort: &str, page: Page| -> Vec<i64> {
+            db.list_stories(None, None, None, &Filter::default(), sort.parse().unwrap(), page)
+                .unwrap().iter().map(|s| s.id).collect()
+        };
+
+        assert_eq!(ids("title", Page::default()), vec![a, b, c]);
+        assert_eq!(ids("priority:desc", Page::default()), vec![c, b, a]);
+        assert_eq!(ids("priority", Page::default()), vec![a, b, c]);
+        assert_eq!(ids("id", Page { limit: Some(2), offset: 1, after: None }), vec![a, c]);
+        assert_eq!(ids("id", Page { offset: 2, ..Default::default() }), vec![c]);
+        // Keyset pages continue after the cursor in the listing's order
+        assert_eq!(ids("priority:desc", Page { limit: Some(1), offset: 0, after: Some(c) }), vec![b]);
+        assert_eq!(ids("priority:desc", Page { limit: None, offset: 0, after: Some(b) }), vec![a]);
+        assert_eq!(ids("title", Page { limit: None, offset: 0, after: Some(b) }), vec![c]);
+    }
+
+    #[test]
+    fn column_sort_is_persisted() {
+        let db = test_db();
+        let todo = Status::new("todo");
+        let sort: Sort = "priority:desc".parse().unwrap();
+        let id = db.get_column(&todo).unwrap().id;
+        assert_eq!(db.set_column_sort(id, sort).unwrap(), Sort::default());
+        assert_eq!(db.get_column(&todo).unwrap().sort, sort);
+        assert_eq!(db.get_column(&Status::new("done")).unwrap().sort, Sort::default());
+        assert!(db.set_column_sort(999, sort).is_err());
+
+        db.update_column(&todo, Some("Backlog"), None, None, Some(Sort::default())).unwrap();
+        assert_eq!(db.get_column(&todo).unwrap().sort, Sort::default());
+        db.conn.execute("UPDATE workflow_columns SET sort = 'bogus' WHERE key = 'todo'", []).unwrap();
+        assert!(db.get_column(&todo).is_err());
+    }
+
+    #[test]
+    fn ranks_order_cards_across_status_moves() {
+        let db = test_db();
+        let todo = Status::new("todo");
+        let [a, b, c] = ["A", "B", "C"].map(|t| db.create_story(t, "", None, &todo, Priority::Medium).unwrap());
+        let ids = |status: &Status| -> Vec<i64> {
+            db.list_stories_by_status(status, None, &Filter::default(), Sort::default()).unwrap().iter().map(|s| s.id).collect()
+        };
+        assert_eq!(ids(&todo), vec![a, b, c]);
+
+        // Each move reports where the story came from, for undo
+        assert_eq!(db.rank_story(c, a, false).unwrap(), (b, true));
+        assert_eq!(ids(&todo), vec![c, a, b]);
+        assert_eq!(db.rank_story(c, b, true).unwrap(), (a, false));
+        assert_eq!(ids(&todo), vec![a, b, c]);
+
+        // Moving two cards to another column keeps their relative order
+        let done = Status::new("done");
+        db.rank_story(b, a, false).unwrap();
+        for id in [a, b] {
+            set_status(&db, id, &done).unwrap();
+        }
+        assert_eq!(ids(&done), vec![b, a]);
+        assert!(matches!(db.rank_story(a, a, true), Err(StackError::Validation(_))));
+        assert!(matches!(db.rank_story(a, 99, true), Err(StackError::NotFound { .. })));
+    }
+
+    #[test]
+    fn next_story_skips_blocked_and_prefers_urgent() {
+        let db = test_db();
+        let todo = Status::new("todo");
+        let low = db.create_story("Low", "", None, &todo, Priority::Low).unwrap();
+        let blocked = db.create_story("Blocked", "", None, &todo, Priority::Critical).unwrap();
+        let first = db.create_story("First", "", None, &todo, Priority::High).unwrap();
+        let second = db.create_story("Second", "", None, &todo, Priority::High).unwrap();
+        db.link_stories(low, blocked).unwrap();
+        let next = |epic, label| db.next_story(&todo, epic, label).unwrap().map(|s| s.id);
+
+        assert_eq!(next(None, None), Some(first));
+        db.rank_story(second, first, false).unwrap();
+        assert_eq!(next(None, None), Some(second));
+        db.add_label(low, "docs").unwrap();
+        assert_eq!(next(None, Some("docs")), Some(low));
+        let epic = db.create_epic("E", "", "red").unwrap();
+        assert_eq!(next(Some(epic), None), None);
+    }
+
+    #[test]
+    fn concurrent_claims_get_different_stories() {
+        let path = std::env::temp_dir().join(format!("stack-claim-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let open = || {
+            let mut db = Database::open(&path).unwrap();
+            db.migrate().unwrap();
+            db
+        };
+        let (todo, doing) = (Status::new("todo"), Status::new("in_progress"));
+        let setup = open();
+        for title in ["A", "B"] {
+            setup.create_story(title, "", None, &todo, Priority::Medium).unwrap();
+        }
+
+        let claims: Vec<Option<i64>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..3).map(|_| scope.spawn(|| {
+                open().claim_next_story(&todo, None, None, &doing, None).unwrap().map(|s| s.id)
+            })).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let mut claimed: Vec<i64> = claims.into_iter().flatten().collect();
+        claimed.sort();
+        assert_eq!(claimed, vec![1, 2]);
+        assert_eq!(setup.story_history(1).unwrap().last().unwrap().to_status, doing);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn claims_belong_to_one_owner_until_they_expire() {
+        let db = test_db();
+        let todo = Status::new("todo");
+        let id = db.create_story("S1", "", None, &todo, Priority::Medium).unwrap();
+
+        let story = db.claim_story(id, "alice", 1800).unwrap();
+        assert_eq!(story.claim.unwrap().owner, "alice");
+        assert!(matches!(db.claim_story(id, "bob", 60), Err(StackError::Conflict(_))));
+        assert!(matches!(db.release_claim(id, "bob"), Err(StackError::Conflict(_))));
+        assert!(matches!(db.renew_claim(id, "bob", 60), Err(StackError::Conflict(_))));
+        db.renew_claim(id, "alice", 3600).unwrap();
+        assert!(db.next_story(&todo, None, None).unwrap().is_none());
+
+        // An expired claim no longer shows and can be taken over
+        db.conn.execute("UPDATE story_claims SET expires_at = datetime('now', '-1 seconds')", []).unwrap();
+        assert_eq!(db.get_story(id).unwrap().claim, None);
+        assert!(matches!(db.renew_claim(id, "alice", 60), Err(StackError::Conflict(_))));
+        assert_eq!(db.claim_story(id, "bob", 60).unwrap().claim.unwrap().owner, "bob");
+        assert_eq!(db.release_claim(id, "bob").unwrap().claim, None);
+        assert!(matches!(db.claim_story(99, "bob", 60), Err(StackError::NotFound { .. })));
+    }
+
+    #[test]
+    fn rank_story_renumbers_when_ranks_collide() {
+        let db = test_db();
+        let todo = Status::new("todo");
+        let [a, b, c] = ["A", "B", "C"].map(|t| db.create_story(t, "", None, &todo, Priority::Medium).unwrap());
+        db.conn.execute("UPDATE stories SET rank = 1", []).unwrap();
+
+        db.rank_story(a, b, true).unwrap();
+        let stories = db.list_stories_by_status(&todo, None, &Filter::default(), Sort::default()).unwrap();
+        assert_eq!(stories.iter().map(|s| s.id).collect::<Vec<_>>(), vec![b, a, c]);
+        assert_eq!(stories.iter().map(|s| s.rank).collect::<Vec<_>>(), vec![2.0, 2.5, 3.0]);
+    }
+
+    #[test]
+    fn list_stories_applies_filter_terms() {
+        let db = test_db();
+        let epic = db.create_epic("E", "", "red").unwrap();
+        let login = db.create_story("Fix login_page", "50% done", Some(epic), &Status::new("todo"), Priority::High).unwrap();
+        let logout = db.create_story("Logout", "", None, &Status::new("in_progress"), Priority::Critical).unwrap();
+        let docs = db.create_story("Docs", "", None, &Status::new("done"), Priority::Low).unwrap();
+        db.link_stories(login, logout).unwrap();
+        db.conn.execute("UPDATE stories SET updated_at = datetime('now', '-30 days') WHERE id = ?1", [docs]).unwrap();
+
+        let ids = |expr: &str| -> Vec<i64> {
+            let filter: Filter = expr.parse().unwrap();
+            db.list_stories(None, None, None, &filter, Sort::default(), Page::default()).unwrap().iter().map(|s| s.id).collect()
+        };
+        assert_eq!(ids("priority>=high"), vec![login, logout]);
+        assert_eq!(ids("priority:low,critical"), vec![logout, docs]);
+        assert_eq!(ids("status:todo,in-progress priority<critical"), vec![login]);
+        assert_eq!(ids("epic:none"), vec![logout, docs]);
+        assert_eq!(ids(&format!("epic:{}", epic)), vec![login]);
+        assert_eq!(ids("title~LOG"), vec![login, logout]);
+        assert_eq!(ids("title~_page"), vec![login]);
+        assert_eq!(ids("title~n_p"), vec![login]);
+        assert_eq!(ids("title~x_"), Vec::<i64>::new());
+        assert_eq!(ids("body~50%"), vec![login]);
+        assert_eq!(ids("updated<7d"), vec![login, logout]);
+        assert_eq!(ids("updated>7d"), vec![docs]);
+        assert_eq!(ids("blocked:true"), vec![logout]);
+        assert_eq!(ids("blocked:false created<1h"), vec![login, docs]);
+    }
+
+    #[test]
+    fn labels_are_removed_with_story() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        db.add_label(sid, "chore").unwrap();
+        db.delete_story(sid).unwrap();
+        assert_eq!(db.list_labels().unwrap()[0].story_count, 0);
+    }
+
+    // --- Search tests ---
+
+    #[test]
+    fn fts_query_quotes_terms() {
+        assert_eq!(fts_query("login bug"), Some("\"login\"* \"bug\"*".to_string()));
+        assert_eq!(fts_query("say \"hi\" OR"), Some("\"say\"* \"\"\"hi\"\"\"* \"OR\"*".to_string()));
+        assert_eq!(fts_query("   "), None);
+    }
+
+    #[test]
+    fn search_matches_title_description_and_tasks() {
+        let db = test_db();
+        let a = db.create_story("Fix login redirect", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let b = db.create_story("Session cleanup", "Users stay logged in forever", None, &Status::new("done"), Priority::Low).unwrap();
+        let c = db.create_story("Onboarding", "", None, &Status::new("in_progress"), Priority::High).unwrap();
+        db.create_task(c, "Write login copy").unwrap();
+
+        let ids: Vec<i64> = db.search_stories("login").unwrap().iter().map(|h| h.story.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&a) && ids.contains(&c));
+
+        let hits = db.search_stories("logged").unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].story.id, b);
+        assert!(hits[0].snippet.contains("[logged]"));
+
+        assert!(db.search_stories("").unwrap().is_empty());
+        assert!(db.search_stories("\"(*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_index_follows_updates_and_deletes() {
+        let db = test_db();
+        let sid = db.create_story("Alpha", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let tid = db.create_task(sid, "Gamma step").unwrap();
+        assert_eq!(db.search_stories("gamma").unwrap().len(), 1);
+
+        db.delete_task(tid).unwrap();
+        assert!(db.search_stories("gamma").unwrap().is_empty());
+
+        db.update_story(sid, &StoryPatch { title: Some("Beta".to_string()), ..Default::default() }).unwrap();
+        assert!(db.search_stories("alpha").unwrap().is_empty());
+        assert_eq!(db.search_stories("beta").unwrap().len(), 1);
+
+        db.delete_story(sid).unwrap();
+        assert!(db.search_stories("beta").unwrap().is_empty());
+    }
+
     #[test]
     fn delete_story() {
         let db = test_db();
-        let sid = db.create_story("S1", "", None, Status::ToDo, Priority::Medium).unwrap();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
         db.delete_story(sid).unwrap();
-        let stories = db.list_stories_by_status(Status::ToDo, None).unwrap();
+        let stories = db.list_stories_by_status(&Status::new("todo"), None, &Filter::default(), Sort::default()).unwrap();
         assert_eq!(stories.len(), 0);
     }
 
     #[test]
-    fn update_story_description() {
+    fn restore_deleted_story() {
+        let db = test_db();
+        let blocker = db.create_story("Blocker", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let sid = db.create_story("S1", "body", None, &Status::new("todo"), Priority::High).unwrap();
+        set_status(&db, sid, &Status::new("in_progress")).unwrap();
+        db.create_task(sid, "T1").unwrap();
+        db.add_label(sid, "backend").unwrap();
+        db.link_stories(blocker, sid).unwrap();
+
+        let snapshot = db.story_snapshot(sid).unwrap();
+        db.delete_story(sid).unwrap();
+        db.restore_story(&snapshot).unwrap();
+
+        let story = db.get_story(sid).unwrap();
+        assert_eq!(story.title, "S1");
+        assert_eq!(story.description, "body");
+        assert_eq!(story.status, Status::new("in_progress"));
+        assert_eq!(story.labels, vec!["backend"]);
+        assert_eq!(story.blocked_by, vec![blocker]);
+        assert_eq!(db.list_tasks(sid).unwrap().len(), 1);
+        assert_eq!(db.story_history(sid).unwrap().len(), 2);
+        assert_eq!(db.search_stories("S1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn task_progress_and_reordering() {
         let db = test_db();
-        let sid = db.create_story("S1", "", None, Status::ToDo, Priority::Medium).unwrap();
-        db.update_story_description(sid, "# Heading\n\nSome **markdown** body").unwrap();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let t1 = db.create_task(sid, "T1").unwrap();
+        let t2 = db.create_task(sid, "T2").unwrap();
+        db.create_task(sid, "T3").unwrap();
+        db.toggle_task(t2).unwrap();
+
         let story = db.get_story(sid).unwrap();
+        assert_eq!((story.tasks_done, story.task_count), (1, 3));
+
+        db.swap_tasks(t1, t2).unwrap();
+        db.rename_task(t1, "First").unwrap();
+        let titles: Vec<String> = db.list_tasks(sid).unwrap().into_iter().map(|t| t.title).collect();
+        assert_eq!(titles, vec!["T2", "First", "T3"]);
+
+        let task = db.get_task(t2).unwrap();
+        db.delete_task(t2).unwrap();
+        db.restore_task(&task).unwrap();
+        assert_eq!(db.list_tasks(sid).unwrap()[0].title, "T2");
+    }
+
+    #[test]
+    fn move_task_renumbers_siblings() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let ids: Vec<i64> = ["A", "B", "C"].iter().map(|t| db.create_task(sid, t).unwrap()).collect();
+        let titles = || db.list_tasks(sid).unwrap().into_iter().map(|t| t.title).collect::<Vec<_>>();
+
+        assert_eq!(db.move_task(ids[2], 0).unwrap().sort_order, 0);
+        assert_eq!(titles(), vec!["C", "A", "B"]);
+        db.move_task(ids[2], 99).unwrap();
+        assert_eq!(titles(), vec!["A", "B", "C"]);
+        let orders: Vec<i64> = db.list_tasks(sid).unwrap().iter().map(|t| t.sort_order).collect();
+        assert_eq!(orders, vec![0, 1, 2]);
+        assert!(db.move_task(999, 0).is_err());
+    }
+
+    #[test]
+    fn set_task_done_is_idempotent() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let tid = db.create_task(sid, "T1").unwrap();
+        assert!(db.set_task_done(tid, true).unwrap().done);
+        assert!(db.set_task_done(tid, true).unwrap().done);
+        assert!(!db.set_task_done(tid, false).unwrap().done);
+    }
+
+    #[test]
+    fn rename_task_rejects_blank_titles() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let tid = db.create_task(sid, "T1").unwrap();
+        assert!(matches!(db.rename_task(tid, "  "), Err(StackError::Validation(_))));
+        assert_eq!(db.get_task(tid).unwrap().title, "T1");
+        assert_eq!(db.rename_task(tid, " Renamed ").unwrap().title, "Renamed");
+        assert!(matches!(db.rename_task(999, "X"), Err(StackError::NotFound { entity: "task", .. })));
+    }
+
+    #[test]
+    fn update_story_applies_patch() {
+        let db = test_db();
+        let eid = db.create_epic("E1", "", "white").unwrap();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let story = db.update_story(sid, &StoryPatch {
+            title: Some("  Renamed ".to_string()),
+            description: Some("# Heading\n\nSome **markdown** body".to_string()),
+            status: Some(Status::new("in_progress")),
+            priority: Some(Priority::High),
+            epic_id: Some(Some(eid)),
+        }).unwrap();
+        assert_eq!(story.title, "Renamed");
         assert_eq!(story.description, "# Heading\n\nSome **markdown** body");
+        assert_eq!(story.status, Status::new("in_progress"));
+        assert_eq!(story.priority, Priority::High);
+        assert_eq!(story.epic_id, Some(eid));
+        assert_eq!(db.story_history(sid).unwrap().len(), 2);
+
+        let story = db.update_story(sid, &StoryPatch { epic_id: Some(None), ..Default::default() }).unwrap();
+        assert_eq!(story.epic_id, None);
+    }
+
+    #[test]
+    fn update_story_validates_before_writing() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+
+        let bad_title = StoryPatch { title: Some(" ".to_string()), priority: Some(Priority::High), ..Default::default() };
+        assert!(matches!(db.update_story(sid, &bad_title), Err(StackError::Validation(_))));
+        let bad_epic = StoryPatch { priority: Some(Priority::High), epic_id: Some(Some(99)), ..Default::default() };
+        assert!(matches!(db.update_story(sid, &bad_epic), Err(StackError::NotFound { entity: "epic", id: Key::Id(99) })));
+        let bad_status = StoryPatch { status: Some(Status::new("qa")), ..Default::default() };
+        assert!(matches!(db.update_story(sid, &bad_status), Err(StackError::Validation(_))));
+        assert_eq!(db.get_story(sid).unwrap().priority, Priority::Medium);
+
+        let missing = db.update_story(999, &StoryPatch::default());
+        assert!(matches!(missing, Err(StackError::NotFound { entity: "story", id: Key::Id(999) })));
     }
 }
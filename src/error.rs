@@ -0,0 +1,158 @@
+use std::fmt;
+use serde::Serialize;
+
+/// How a missing entity was looked up: by numeric id or by name/key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Key {
+    Id(i64),
+    Name(String),
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Id(id) => write!(f, "{}", id),
+            Key::Name(name) => write!(f, "'{}'", name),
+        }
+    }
+}
+
+impl From<i64> for Key {
+    fn from(id: i64) -> Self {
+        Key::Id(id)
+    }
+}
+
+impl From<&str> for Key {
+    fn from(name: &str) -> Self {
+        Key::Name(name.to_string())
+    }
+}
+
+/// Errors surfaced to CLI callers, each with a stable code and exit status.
+#[derive(Debug)]
+pub enum StackError {
+    NotFound { entity: &'static str, id: Key },
+    /// Input that was rejected before touching the database.
+    Validation(String),
+    /// The request is valid but clashes with the current state (e.g. a WIP
+    /// limit in strict mode, or removing a column that still has stories).
+    Conflict(String),
+    Db(rusqlite::Error),
+    Io(std::io::Error),
+    /// A result could not be encoded as JSON; a bug rather than bad input.
+    Internal(serde_json::Error),
+}
+
+impl StackError {
+    pub fn not_found(entity: &'static str, id: impl Into<Key>) -> Self {
+        StackError::NotFound { entity, id: id.into() }
+    }
+
+    /// Machine-readable category, stable across releases.
+    pub fn code(&self) -> &'static str {
+        match self {
+            StackError::NotFound { .. } => "not_found",
+            StackError::Validation(_) => "validation",
+            StackError::Conflict(_) => "conflict",
+            StackError::Db(_) => "db",
+            StackError::Io(_) => "io",
+            StackError::Internal(_) => "internal",
+        }
+    }
+
+    /// Process exit status for the CLI; 2 is left to clap's usage errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            StackError::NotFound { .. } => 3,
+            StackError::Validation(_) => 4,
+            StackError::Conflict(_) => 5,
+            StackError::Db(_) => 6,
+            StackError::Io(_) => 7,
+            StackError::Internal(_) => 8,
+        }
+    }
+
+    /// The `error` object printed by the CLI.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!({ "code": self.code(), "message": self.to_string() });
+        if let StackError::NotFound { entity, id } = self {
+            value["entity"] = serde_json::json!(entity);
+            value["id"] = serde_json::json!(id);
+        }
+        value
+    }
+}
+
+impl fmt::Display for StackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackError::NotFound { entity, id } => write!(f, "{} {} not found", entity, id),
+            StackError::Validation(msg) | StackError::Conflict(msg) => write!(f, "{}", msg),
+            StackError::Db(e) => write!(f, "{}", e),
+            StackError::Io(e) => write!(f, "{}", e),
+            StackError::Internal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StackError::Db(e) => Some(e),
+            StackError::Io(e) => Some(e),
+            StackError::Internal(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StackError {
+    fn from(e: rusqlite::Error) -> Self {
+        StackError::Db(e)
+    }
+}
+
+impl From<std::io::Error> for StackError {
+    fn from(e: std::io::Error) -> Self {
+        StackError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for StackError {
+    fn from(e: serde_json::Error) -> Self {
+        StackError::Internal(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_found_json_names_the_entity() {
+        let err = StackError::not_found("story", 7);
+        assert_eq!(err.to_json(), serde_json::json!({
+            "code": "not_found", "message": "story 7 not found", "entity": "story", "id": 7,
+        }));
+        assert_eq!(StackError::not_found("board", "side").to_string(), "board 'side' not found");
+    }
+
+    #[test]
+    fn categories_have_distinct_exit_codes() {
+        let errors = [
+            StackError::not_found("task", 1),
+            StackError::Validation(String::new()),
+            StackError::Conflict(String::new()),
+            StackError::Db(rusqlite::Error::QueryReturnedNoRows),
+            StackError::Io(std::io::Error::other("disk")),
+            StackError::Internal(serde_json::from_str::<i64>("x").unwrap_err()),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(StackError::exit_code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert_eq!(errors[1].to_json(), serde_json::json!({ "code": "validation", "message": "" }));
+    }
+}
@@ -7,6 +7,8 @@ pub fn handle_board_key(key: KeyEvent) -> Option<Action> {
         KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
         KeyCode::Char('a') => Some(Action::MoveStoryLeft),
         KeyCode::Char('s') => Some(Action::MoveStoryRight),
+        KeyCode::Char('K') => Some(Action::MoveCardUp),
+        KeyCode::Char('J') => Some(Action::MoveCardDown),
         KeyCode::Left | KeyCode::Char('h') => Some(Action::MoveLeft),
         KeyCode::Right | KeyCode::Char('l') => Some(Action::MoveRight),
         KeyCode::Down | KeyCode::Char('j') => Some(Action::MoveDown),
@@ -15,6 +17,16 @@ pub fn handle_board_key(key: KeyEvent) -> Option<Action> {
         KeyCode::Char('n') => Some(Action::NewStory),
         KeyCode::Char('d') => Some(Action::DeleteStory),
         KeyCode::Char('e') => Some(Action::OpenEpicList),
+        KeyCode::Char('E') => Some(Action::OpenEpicPicker),
+        KeyCode::Char('+') | KeyCode::Char('=') => Some(Action::RaisePriority),
+        KeyCode::Char('-') => Some(Action::LowerPriority),
+        KeyCode::Char('b') => Some(Action::OpenBoardList),
+        KeyCode::Char('u') => Some(Action::Undo),
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Redo),
+        KeyCode::Char('/') => Some(Action::Search),
+        KeyCode::Char('f') => Some(Action::Filter),
+        KeyCode::Char('o') => Some(Action::CycleSort),
+        KeyCode::Esc => Some(Action::ClearSearch),
         _ => None,
     }
 }
@@ -26,12 +38,49 @@ pub fn handle_detail_key(key: KeyEvent) -> Option<Action> {
         KeyCode::Up | KeyCode::Char('k') => Some(Action::MoveUp),
         KeyCode::Char('e') => Some(Action::EditStoryTitle),
         KeyCode::Char('b') => Some(Action::EditStoryBody),
+        KeyCode::Char('E') => Some(Action::OpenEpicPicker),
+        KeyCode::Char('+') | KeyCode::Char('=') => Some(Action::RaisePriority),
+        KeyCode::Char('-') => Some(Action::LowerPriority),
+        KeyCode::Tab => Some(Action::SwitchDetailFocus),
+        KeyCode::Char('t') => Some(Action::NewTask),
+        KeyCode::Char(' ') => Some(Action::ToggleTask),
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Redo),
+        KeyCode::Char('r') => Some(Action::RenameTask),
+        KeyCode::Char('d') => Some(Action::DeleteTask),
+        KeyCode::Char('K') => Some(Action::MoveTaskUp),
+        KeyCode::Char('J') => Some(Action::MoveTaskDown),
+        KeyCode::Char('u') => Some(Action::Undo),
         KeyCode::Char('q') => Some(Action::Quit),
         _ => None,
     }
 }
 
 pub fn handle_epic_list_key(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Esc => Some(Action::CloseDetail),
+        KeyCode::Down | KeyCode::Char('j') => Some(Action::MoveDown),
+        KeyCode::Up | KeyCode::Char('k') => Some(Action::MoveUp),
+        KeyCode::Enter => Some(Action::InputConfirm),
+        KeyCode::Char('n') => Some(Action::NewEpic),
+        KeyCode::Char('r') => Some(Action::RenameEpic),
+        KeyCode::Char('c') => Some(Action::RecolorEpic),
+        KeyCode::Char('d') => Some(Action::DeleteEpic),
+        KeyCode::Char('q') => Some(Action::Quit),
+        _ => None,
+    }
+}
+
+pub fn handle_epic_picker_key(key: KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Esc => Some(Action::InputCancel),
+        KeyCode::Down | KeyCode::Char('j') => Some(Action::MoveDown),
+        KeyCode::Up | KeyCode::Char('k') => Some(Action::MoveUp),
+        KeyCode::Enter => Some(Action::InputConfirm),
+        _ => None,
+    }
+}
+
+pub fn handle_board_list_key(key: KeyEvent) -> Option<Action> {
     match key.code {
         KeyCode::Esc => Some(Action::CloseDetail),
         KeyCode::Down | KeyCode::Char('j') => Some(Action::MoveDown),
@@ -89,15 +138,25 @@ mod tests {
     fn board_story_movement() {
         assert_eq!(handle_board_key(key(KeyCode::Char('a'))), Some(Action::MoveStoryLeft));
         assert_eq!(handle_board_key(key(KeyCode::Char('s'))), Some(Action::MoveStoryRight));
+        assert_eq!(handle_board_key(key(KeyCode::Char('K'))), Some(Action::MoveCardUp));
+        assert_eq!(handle_board_key(key(KeyCode::Char('J'))), Some(Action::MoveCardDown));
     }
 
     #[test]
     fn board_actions() {
         assert_eq!(handle_board_key(key(KeyCode::Enter)), Some(Action::OpenDetail));
         assert_eq!(handle_board_key(key(KeyCode::Char('n'))), Some(Action::NewStory));
+        assert_eq!(handle_board_key(key(KeyCode::Char('o'))), Some(Action::CycleSort));
         assert_eq!(handle_board_key(key(KeyCode::Char('q'))), Some(Action::Quit));
     }
 
+    #[test]
+    fn board_search() {
+        assert_eq!(handle_board_key(key(KeyCode::Char('/'))), Some(Action::Search));
+        assert_eq!(handle_board_key(key(KeyCode::Char('f'))), Some(Action::Filter));
+        assert_eq!(handle_board_key(key(KeyCode::Esc)), Some(Action::ClearSearch));
+    }
+
     #[test]
     fn detail_navigation() {
         assert_eq!(handle_detail_key(key(KeyCode::Down)), Some(Action::MoveDown));
@@ -119,6 +178,54 @@ mod tests {
         assert_eq!(handle_epic_list_key(key(KeyCode::Char('k'))), Some(Action::MoveUp));
     }
 
+    #[test]
+    fn detail_task_keys() {
+        assert_eq!(handle_detail_key(key(KeyCode::Tab)), Some(Action::SwitchDetailFocus));
+        assert_eq!(handle_detail_key(key(KeyCode::Char('t'))), Some(Action::NewTask));
+        assert_eq!(handle_detail_key(key(KeyCode::Char(' '))), Some(Action::ToggleTask));
+        assert_eq!(handle_detail_key(key(KeyCode::Char('r'))), Some(Action::RenameTask));
+        assert_eq!(handle_detail_key(key(KeyCode::Char('d'))), Some(Action::DeleteTask));
+        assert_eq!(handle_detail_key(key(KeyCode::Char('J'))), Some(Action::MoveTaskDown));
+        assert_eq!(handle_detail_key(key(KeyCode::Char('K'))), Some(Action::MoveTaskUp));
+    }
+
+    #[test]
+    fn undo_keys() {
+        let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!(handle_board_key(key(KeyCode::Char('u'))), Some(Action::Undo));
+        assert_eq!(handle_board_key(ctrl_r), Some(Action::Redo));
+        assert_eq!(handle_detail_key(key(KeyCode::Char('u'))), Some(Action::Undo));
+        assert_eq!(handle_detail_key(ctrl_r), Some(Action::Redo));
+        assert_eq!(handle_board_key(key(KeyCode::Char('r'))), None);
+    }
+
+    #[test]
+    fn epic_list_keys() {
+        assert_eq!(handle_epic_list_key(key(KeyCode::Char('n'))), Some(Action::NewEpic));
+        assert_eq!(handle_epic_list_key(key(KeyCode::Char('r'))), Some(Action::RenameEpic));
+        assert_eq!(handle_epic_list_key(key(KeyCode::Char('c'))), Some(Action::RecolorEpic));
+        assert_eq!(handle_epic_list_key(key(KeyCode::Char('d'))), Some(Action::DeleteEpic));
+    }
+
+    #[test]
+    fn priority_and_epic_keys() {
+        for handle in [handle_board_key, handle_detail_key] {
+            assert_eq!(handle(key(KeyCode::Char('+'))), Some(Action::RaisePriority));
+            assert_eq!(handle(key(KeyCode::Char('-'))), Some(Action::LowerPriority));
+            assert_eq!(handle(key(KeyCode::Char('E'))), Some(Action::OpenEpicPicker));
+        }
+        assert_eq!(handle_epic_picker_key(key(KeyCode::Enter)), Some(Action::InputConfirm));
+        assert_eq!(handle_epic_picker_key(key(KeyCode::Esc)), Some(Action::InputCancel));
+    }
+
+    #[test]
+    fn board_list_keys() {
+        assert_eq!(handle_board_key(key(KeyCode::Char('b'))), Some(Action::OpenBoardList));
+        assert_eq!(handle_board_list_key(key(KeyCode::Char('j'))), Some(Action::MoveDown));
+        assert_eq!(handle_board_list_key(key(KeyCode::Enter)), Some(Action::InputConfirm));
+        assert_eq!(handle_board_list_key(key(KeyCode::Esc)), Some(Action::CloseDetail));
+    }
+
     #[test]
     fn input_mode() {
         assert_eq!(handle_input_key(key(KeyCode::Char('a'))), Some(Action::InputChar('a')));
@@ -0,0 +1,113 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::error::StackError;
+
+/// Directory that marks a repository-local board, like `.git`.
+pub const LOCAL_DIR: &str = ".stack";
+pub const DB_FILE: &str = "stack.db";
+/// Environment variable that overrides database discovery.
+pub const DB_ENV: &str = "STACK_DB";
+
+/// Pick the database to open: `--db`, then `env` (the value of `STACK_DB`),
+/// then the nearest `.stack/stack.db` above `cwd`, then `global`.
+pub fn resolve(
+    flag: Option<PathBuf>,
+    env: Option<OsString>,
+    cwd: &Path,
+    global: impl FnOnce() -> Result<PathBuf, StackError>,
+) -> Result<PathBuf, StackError> {
+    if let Some(path) = flag {
+        return Ok(path);
+    }
+    if let Some(path) = env.filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    if let Some(path) = find_local(cwd) {
+        return Ok(path);
+    }
+    global()
+}
+
+/// Walk up from `start` looking for `.stack/stack.db`.
+pub fn find_local(start: &Path) -> Option<PathBuf> {
+    start.ancestors()
+        .map(|dir| dir.join(LOCAL_DIR).join(DB_FILE))
+        .find(|path| path.is_file())
+}
+
+/// The per-user database, creating its directory if needed.
+pub fn global_db() -> Result<PathBuf, StackError> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "could not determine data directory"))?
+        .join("stack");
+    std::fs::create_dir_all(&data_dir)?;
+    Ok(data_dir.join(DB_FILE))
+}
+
+/// Create `dir/.stack` and return the database path inside it.
+pub fn init(dir: &Path) -> std::io::Result<PathBuf> {
+    let local_dir = dir.join(LOCAL_DIR);
+    std::fs::create_dir_all(&local_dir)?;
+    Ok(local_dir.join(DB_FILE))
+}
+
+/// Shorten a path for display by replacing the home directory with `~`.
+pub fn display(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("stack-location-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn finds_nearest_local_database() {
+        let root = scratch_dir("nearest");
+        let nested = root.join("src").join("deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_local(&nested), None);
+
+        let db = init(&root).unwrap();
+        std::fs::write(&db, b"").unwrap();
+        assert_eq!(find_local(&nested), Some(db.clone()));
+
+        let inner = init(&root.join("src")).unwrap();
+        std::fs::write(&inner, b"").unwrap();
+        assert_eq!(find_local(&nested), Some(inner));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn resolve_prefers_flag_then_env_then_local_then_global() {
+        let root = scratch_dir("resolve");
+        let nested = root.join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        let global = || Ok(PathBuf::from("/global.db"));
+        let pick = |flag: Option<&str>, env: Option<&str>| {
+            resolve(flag.map(PathBuf::from), env.map(OsString::from), &nested, global).unwrap()
+        };
+
+        assert_eq!(pick(None, None), PathBuf::from("/global.db"));
+        assert_eq!(pick(None, Some("")), PathBuf::from("/global.db"));
+
+        let local = init(&root).unwrap();
+        std::fs::write(&local, b"").unwrap();
+        assert_eq!(pick(None, None), local);
+        assert_eq!(pick(None, Some("/env.db")), PathBuf::from("/env.db"));
+        assert_eq!(pick(Some("/flag.db"), Some("/env.db")), PathBuf::from("/flag.db"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
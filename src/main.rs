@@ -2,53 +2,121 @@ mod actions;
 mod app;
 mod cli;
 mod cli_handler;
+mod color;
 mod db;
 mod editor;
+mod error;
 mod input;
+mod location;
 mod models;
+mod output;
 mod ui;
+mod undo;
 
-use std::io;
+use std::io::{self, IsTerminal};
 use std::time::{Duration, Instant};
 use clap::Parser;
 use crossterm::event::{self, Event, KeyEventKind};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use crossterm::execute;
-use ratatui::backend::CrosstermBackend;
+use ratatui::backend::{CrosstermBackend, TestBackend};
 use ratatui::Terminal;
 
 use actions::Action;
-use app::{App, ConfirmAction, InputTarget, Mode};
+use app::{App, ConfirmAction, DetailFocus, InputTarget, Mode};
 use db::Database;
-use models::Status;
+use error::StackError;
+use output::{Format, View};
+use models::{Filter, SortField, Status, StoryPatch};
+use undo::Op;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = cli::Cli::parse();
+    let format = cli.format.unwrap_or_else(Format::detect);
 
-    // Resolve data directory
-    let data_dir = dirs::data_dir()
-        .expect("Could not determine data directory")
-        .join("stack");
-    std::fs::create_dir_all(&data_dir)?;
+    match cli.command {
+        // `stack init` creates a local database instead of discovering one
+        Some(cli::Command::Init) => report(init(cli.db).map(|v| output::render(&View::Other, &v.into(), format)), format),
+        Some(command) => report(run_cli(command, cli.db, cli.board.as_deref(), format), format),
+        None => {
+            let db_path = db_path(cli.db)?;
+            let mut database = Database::open(&db_path)?;
+            database.migrate()?;
+            database.use_board(cli.board.as_deref())?;
+            run_tui(database, &db_path)?;
+        }
+    }
+    Ok(())
+}
 
-    // Open database
-    let mut database = Database::open(&data_dir.join("stack.db"))?;
-    database.migrate()?;
+/// Print a subcommand's formatted result, or its error with a distinct exit code.
+fn report(result: Result<String, StackError>, format: Format) {
+    match result {
+        Ok(text) => println!("{}", text),
+        Err(e) => {
+            eprintln!("{}", output::render_error(&e, format));
+            std::process::exit(e.exit_code());
+        }
+    }
+}
+
+/// The database to open for `--db` from the current directory and environment.
+fn db_path(flag: Option<std::path::PathBuf>) -> Result<std::path::PathBuf, StackError> {
+    location::resolve(flag, std::env::var_os(location::DB_ENV), &std::env::current_dir()?, location::global_db)
+}
 
-    // If a subcommand was given, run CLI mode
-    if let Some(command) = cli.command {
-        if let Err(e) = cli_handler::run(command, &database) {
-            eprintln!("{}", serde_json::json!({ "error": e.to_string() }));
-            std::process::exit(1);
+/// Open the database and run a subcommand; `stack db` inspects and applies
+/// migrations itself.
+fn run_cli(command: cli::Command, path: Option<std::path::PathBuf>, board: Option<&str>, format: Format) -> Result<String, StackError> {
+    let db_path = db_path(path)?;
+    let mut database = Database::open(&db_path)?;
+    if !matches!(command, cli::Command::Db { .. }) {
+        database.migrate()?;
+        database.use_board(board)?;
+    }
+    if let cli::Command::Board { action: None, render: true, epic, label, width } = &command {
+        return Ok(render_board(&database, &db_path, *epic, label.as_deref(), *width));
+    }
+    let view = View::of(&command, &database)?;
+    let reply = cli_handler::run(command, &mut database)?;
+    Ok(output::render(&view, &reply, format))
+}
+
+/// Draw the board's header and columns with the TUI's widgets, sized to fit every card.
+fn render_board(db: &Database, db_path: &std::path::Path, epic: Option<i64>, label: Option<&str>, width: Option<u16>) -> String {
+    let mut app = App::new();
+    app.db_path = location::display(db_path);
+    app.epic_filter = epic;
+    refresh_board(db, &mut app);
+    if let Some(label) = label {
+        for stories in &mut app.columns {
+            stories.retain(|s| s.labels.iter().any(|l| l == label));
         }
-        return Ok(());
     }
 
-    // Otherwise, launch TUI
-    run_tui(database)
+    let terminal_width = || terminal::size().ok().filter(|_| io::stdout().is_terminal()).map(|(w, _)| w);
+    let width = width.or_else(terminal_width).unwrap_or(120);
+    let cards = app.columns.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    // Header, column borders and one line per card
+    let height = 2 + 2 + cards as u16;
+    let Ok(mut terminal) = Terminal::new(TestBackend::new(width, height));
+    let Ok(frame) = terminal.draw(|frame| ui::render_board(&app, frame));
+    output::buffer_text(frame.buffer, io::stdout().is_terminal())
 }
 
-fn run_tui(database: Database) -> Result<(), Box<dyn std::error::Error>> {
+/// Create `.stack/stack.db` in the current directory (or at `--db`).
+fn init(path: Option<std::path::PathBuf>) -> Result<serde_json::Value, StackError> {
+    let path = match path {
+        Some(path) => path,
+        None => location::init(&std::env::current_dir()?)?,
+    };
+    let created = !path.exists();
+    let mut database = Database::open(&path)?;
+    database.migrate()?;
+    Ok(serde_json::json!({ "path": path, "created": created }))
+}
+
+fn run_tui(mut database: Database, db_path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
     // Setup terminal
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -58,6 +126,7 @@ fn run_tui(database: Database) -> Result<(), Box<dyn std::error::Error>> {
 
     // Init app state
     let mut app = App::new();
+    app.db_path = location::display(db_path);
     refresh_board(&database, &mut app);
 
     // Auto-refresh every 2 seconds so external changes (e.g. from CLI/agents) appear
@@ -76,12 +145,14 @@ fn run_tui(database: Database) -> Result<(), Box<dyn std::error::Error>> {
                 Mode::Board => input::handle_board_key(key),
                 Mode::Detail => input::handle_detail_key(key),
                 Mode::EpicList => input::handle_epic_list_key(key),
+                Mode::BoardList => input::handle_board_list_key(key),
+                Mode::EpicPicker => input::handle_epic_picker_key(key),
                 Mode::Input(_) => input::handle_input_key(key),
                 Mode::Confirm(_) => input::handle_confirm_key(key),
             };
 
             if let Some(action) = action {
-                handle_action(&database, &mut app, action, &mut terminal);
+                handle_action(&mut database, &mut app, action, &mut terminal);
                 last_refresh = Instant::now();
             }
         }
@@ -103,7 +174,7 @@ fn run_tui(database: Database) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) {
+fn handle_action(db: &mut Database, app: &mut App, action: Action, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) {
     app.status_message = None;
 
     match action {
@@ -122,65 +193,89 @@ fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Te
         }
         Action::MoveUp => match app.mode {
             Mode::Board => app.move_card_up(),
+            Mode::Detail if app.detail_focus == DetailFocus::Tasks => app.move_task_cursor_up(),
             Mode::Detail => {
                 app.scroll_offset = app.scroll_offset.saturating_sub(1);
             }
-            Mode::EpicList => {
-                if app.list_selection > 0 {
-                    app.list_selection -= 1;
-                }
+            Mode::EpicList | Mode::EpicPicker | Mode::BoardList => {
+                app.list_selection = app.list_selection.saturating_sub(1);
             }
             _ => {}
         },
         Action::MoveDown => match app.mode {
             Mode::Board => app.move_card_down(),
+            Mode::Detail if app.detail_focus == DetailFocus::Tasks => app.move_task_cursor_down(),
             Mode::Detail => {
                 app.scroll_offset = app.scroll_offset.saturating_add(1);
             }
-            Mode::EpicList => {
+            Mode::EpicList | Mode::EpicPicker => {
                 let max = app.epics.len();
                 if app.list_selection < max {
                     app.list_selection += 1;
                 }
             }
+            Mode::BoardList if app.list_selection + 1 < app.boards.len() => {
+                app.list_selection += 1;
+            }
             _ => {}
         },
 
         // Move story between columns
-        Action::MoveStoryLeft => {
+        Action::MoveStoryLeft | Action::MoveStoryRight => {
+            let target = app.selected_story().and_then(|story| match action {
+                Action::MoveStoryLeft => story.status.prev(&app.workflow),
+                _ => story.status.next(&app.workflow),
+            });
             if let Some(story) = app.selected_story()
-                && let Some(prev) = story.status.prev()
+                && let Some(target) = target
             {
-                let id = story.id;
-                if let Err(e) = db.update_story_status(id, prev) {
-                    app.status_message = Some(format!("Error: {}", e));
+                if story.blocked && story.status.starts_work(&target, &app.workflow) {
+                    let blockers: Vec<String> = story.blocked_by.iter().map(|id| format!("#{}", id)).collect();
+                    app.status_message = Some(format!("Blocked by {} — finish those first", blockers.join(", ")));
+                    return;
                 }
-                refresh_board(db, app);
+                move_story(db, app, story.id, &target);
             }
         }
-        Action::MoveStoryRight => {
-            if let Some(story) = app.selected_story()
-                && let Some(next) = story.status.next()
+        Action::MoveCardUp | Action::MoveCardDown => {
+            let col = app.selected_column;
+            let Some(column) = app.workflow.get(col) else { return };
+            if column.sort.field != SortField::Rank {
+                app.status_message = Some(format!("{} is sorted by {} — press o to sort by rank", column.name, column.sort));
+                return;
+            }
+            let card = app.selected_card[col];
+            let down = action == Action::MoveCardDown;
+            let target = if down { Some(card + 1) } else { card.checked_sub(1) };
+            if let Some(target) = target
+                && let (Some(story), Some(other)) = (app.columns[col].get(card), app.columns[col].get(target))
             {
+                // In a rank:desc column, moving down means a lower rank
+                let op = Op::Rank { id: story.id, anchor: other.id, after: down != column.sort.descending };
                 let id = story.id;
-                if let Err(e) = db.update_story_status(id, next) {
-                    app.status_message = Some(format!("Error: {}", e));
-                }
-                refresh_board(db, app);
+                let description = format!("move #{} {}", id, if down { "down" } else { "up" });
+                perform(db, app, description, op);
+                app.select_story(id);
             }
         }
 
         // Open/close views
         Action::OpenDetail => {
             if let Some(story) = app.selected_story().cloned() {
+                app.status_history = db.story_history(story.id).unwrap_or_default();
+                app.tasks = db.list_tasks(story.id).unwrap_or_default();
                 app.current_story = Some(story);
                 app.scroll_offset = 0;
+                app.task_cursor = 0;
+                app.detail_focus = DetailFocus::Body;
                 app.mode = Mode::Detail;
             }
         }
         Action::CloseDetail => {
             app.mode = Mode::Board;
             app.current_story = None;
+            app.status_history.clear();
+            app.tasks.clear();
             refresh_board(db, app);
         }
         Action::OpenEpicList => {
@@ -199,6 +294,107 @@ fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Te
             }
         }
 
+        Action::RaisePriority | Action::LowerPriority => {
+            if let Some(story) = app.focused_story() {
+                let priority = match action {
+                    Action::RaisePriority => story.priority.raised(),
+                    _ => story.priority.lowered(),
+                };
+                if priority == story.priority {
+                    app.status_message = Some(format!("Priority is already {}", priority));
+                    return;
+                }
+                let description = format!("set #{} priority to {}", story.id, priority);
+                let op = Op::Update { id: story.id, patch: StoryPatch { priority: Some(priority), ..Default::default() } };
+                perform(db, app, description, op);
+            }
+        }
+        Action::OpenEpicPicker => {
+            if let Some(epic_id) = app.focused_story().map(|s| s.epic_id) {
+                app.list_selection = epic_id
+                    .and_then(|id| app.epics.iter().position(|e| e.id == id))
+                    .map_or(0, |i| i + 1);
+                app.mode = Mode::EpicPicker;
+            }
+        }
+
+        Action::NewEpic => {
+            app.input_buffer.clear();
+            app.mode = Mode::Input(InputTarget::NewEpic);
+        }
+        Action::RenameEpic => {
+            if let Some(epic) = app.selected_epic() {
+                app.input_buffer = epic.title.clone();
+                app.mode = Mode::Input(InputTarget::RenameEpic);
+            }
+        }
+        Action::RecolorEpic => {
+            if let Some(epic) = app.selected_epic() {
+                let description = format!("recolor {}", epic.title);
+                let op = Op::UpdateEpic { id: epic.id, title: None, color: Some(epic.next_color().to_string()) };
+                perform(db, app, description, op);
+            }
+        }
+        Action::DeleteEpic => {
+            if app.selected_epic().is_some() {
+                app.mode = Mode::Confirm(ConfirmAction::DeleteEpic);
+            }
+        }
+
+        Action::OpenBoardList => {
+            match db.list_boards() {
+                Ok(boards) => {
+                    app.boards = boards;
+                    app.list_selection = app.boards.iter().position(|b| b.id == db.board_id()).unwrap_or(0);
+                    app.mode = Mode::BoardList;
+                }
+                Err(e) => app.status_message = Some(format!("Error: {}", e)),
+            }
+        }
+
+        // Search
+        Action::Search => {
+            app.input_buffer = app.search_query.clone().unwrap_or_default();
+            app.mode = Mode::Input(InputTarget::Search);
+        }
+        Action::CycleSort => {
+            if let Some(column) = app.workflow.get(app.selected_column) {
+                let sort = column.sort.next();
+                let description = format!("sort {} by {}", column.name, sort);
+                let op = Op::SortColumn { column: column.id, sort };
+                match app.undo.perform(db, description, op) {
+                    Ok(()) => app.status_message = Some(format!("{} sorted by {}", column.name, sort)),
+                    Err(e) => app.status_message = Some(format!("Error: {}", e)),
+                }
+                // Keep the same card selected in its new position
+                let selected = app.selected_story().map(|s| s.id);
+                refresh_board(db, app);
+                if let Some(id) = selected {
+                    app.select_story(id);
+                }
+            }
+        }
+        Action::Filter => {
+            app.input_buffer = app.filter.to_string();
+            app.mode = Mode::Input(InputTarget::Filter);
+        }
+        Action::ClearSearch => {
+            let had_filter = !std::mem::take(&mut app.filter).is_empty();
+            if app.search_query.take().is_some() || had_filter {
+                refresh_board(db, app);
+            }
+        }
+
+        // Undo/redo
+        Action::Undo => {
+            let result = app.undo.undo(db);
+            after_undo(db, app, result, "Undid", "Nothing to undo");
+        }
+        Action::Redo => {
+            let result = app.undo.redo(db);
+            after_undo(db, app, result, "Redid", "Nothing to redo");
+        }
+
         // Input mode
         Action::NewStory => {
             app.input_buffer.clear();
@@ -215,7 +411,8 @@ fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Te
                 let id = story.id;
                 match editor::spawn_editor(&story.description) {
                     Ok(Some(text)) => {
-                        if let Err(e) = db.update_story_description(id, &text) {
+                        let op = Op::Update { id, patch: StoryPatch { description: Some(text), ..Default::default() } };
+                        if let Err(e) = app.undo.perform(db, format!("edit body of #{}", id), op) {
                             app.status_message = Some(format!("Error: {}", e));
                         }
                         if let Ok(s) = db.get_story(id) {
@@ -232,9 +429,99 @@ fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Te
                 terminal.clear().ok();
             }
         }
+
+        // Task checklist
+        Action::SwitchDetailFocus => {
+            app.detail_focus = match app.detail_focus {
+                DetailFocus::Body => DetailFocus::Tasks,
+                DetailFocus::Tasks => DetailFocus::Body,
+            };
+        }
+        Action::NewTask => {
+            if app.current_story.is_some() {
+                app.input_buffer.clear();
+                app.mode = Mode::Input(InputTarget::NewTask);
+            }
+        }
+        Action::RenameTask => {
+            if app.detail_focus == DetailFocus::Tasks
+                && let Some(task) = app.selected_task()
+            {
+                app.input_buffer = task.title.clone();
+                app.mode = Mode::Input(InputTarget::RenameTask);
+            }
+        }
+        Action::ToggleTask => {
+            if app.detail_focus == DetailFocus::Tasks
+                && let Some(task) = app.selected_task()
+            {
+                let verb = if task.done { "uncheck" } else { "check" };
+                let description = format!("{} task \"{}\"", verb, task.title);
+                let op = Op::ToggleTask { id: task.id };
+                perform(db, app, description, op);
+            }
+        }
+        Action::DeleteTask => {
+            if app.detail_focus == DetailFocus::Tasks
+                && let Some(task) = app.selected_task()
+            {
+                let description = format!("delete task \"{}\"", task.title);
+                let op = Op::DeleteTask { id: task.id };
+                perform(db, app, description, op);
+            }
+        }
+        Action::MoveTaskUp | Action::MoveTaskDown => {
+            let target = match action {
+                Action::MoveTaskUp => app.task_cursor.checked_sub(1),
+                _ => Some(app.task_cursor + 1),
+            };
+            if app.detail_focus == DetailFocus::Tasks
+                && let Some(target) = target
+                && let (Some(task), Some(other)) = (app.tasks.get(app.task_cursor), app.tasks.get(target))
+            {
+                let description = format!("move task \"{}\"", task.title);
+                let op = Op::SwapTasks { a: task.id, b: other.id };
+                app.task_cursor = target;
+                perform(db, app, description, op);
+            }
+        }
+
         Action::InputChar(c) => app.input_buffer.push(c),
         Action::InputBackspace => { app.input_buffer.pop(); }
         Action::InputConfirm => {
+            // Handle board list selection via InputConfirm
+            if app.mode == Mode::BoardList {
+                if let Some(board) = app.boards.get(app.list_selection) {
+                    db.set_board(board.id);
+                    if let Err(e) = db.set_current_board(board.id) {
+                        app.status_message = Some(format!("Error: {}", e));
+                    }
+                    app.epic_filter = None;
+                    app.search_query = None;
+                    app.filter = Filter::default();
+                    app.selected_column = 0;
+                    app.selected_card.clear();
+                }
+                app.mode = Mode::Board;
+                refresh_board(db, app);
+                return;
+            }
+
+            // Handle epic picker selection via InputConfirm
+            if app.mode == Mode::EpicPicker {
+                let epic = app.selected_epic().map(|e| (e.id, e.title.clone()));
+                if let Some(id) = app.focused_story().map(|s| s.id) {
+                    let description = match &epic {
+                        Some((_, title)) => format!("assign #{} to {}", id, title),
+                        None => format!("clear epic of #{}", id),
+                    };
+                    let patch = StoryPatch { epic_id: Some(epic.map(|(id, _)| id)), ..Default::default() };
+                    perform(db, app, description, Op::Update { id, patch });
+                }
+                app.mode = picker_origin(app);
+                return;
+            }
+
             // Handle epic list selection via InputConfirm
             if app.mode == Mode::EpicList {
                 let eid = if app.list_selection == 0 {
@@ -249,16 +536,49 @@ fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Te
             }
 
             let text = app.input_buffer.clone();
+            if app.mode == Mode::Input(InputTarget::Search) {
+                let query = text.trim();
+                app.search_query = if query.is_empty() { None } else { Some(query.to_string()) };
+                app.input_buffer.clear();
+                app.mode = Mode::Board;
+                refresh_board(db, app);
+                if app.search_query.is_some() {
+                    // Jump to the best match
+                    match app.search_hits.first() {
+                        Some(&id) => { app.select_story(id); }
+                        None => app.status_message = Some("No matching stories".to_string()),
+                    }
+                }
+                return;
+            }
+            if app.mode == Mode::Input(InputTarget::Filter) {
+                match text.parse::<Filter>() {
+                    Ok(filter) => {
+                        app.filter = filter;
+                        app.input_buffer.clear();
+                        app.mode = Mode::Board;
+                        refresh_board(db, app);
+                    }
+                    // Stay in the prompt so the expression can be fixed
+                    Err(e) => app.status_message = Some(format!("Filter error: {}", e)),
+                }
+                return;
+            }
             if !text.is_empty() {
                 match app.mode {
                     Mode::Input(InputTarget::NewStory) => {
-                        let status = Status::all()[app.selected_column];
-                        match db.create_story(&text, "", app.epic_filter, status, models::Priority::Medium) {
+                        let Some(status) = app.workflow.get(app.selected_column).map(|c| c.key.clone()) else {
+                            app.mode = Mode::Board;
+                            return;
+                        };
+                        match db.create_story(&text, "", app.epic_filter, &status, models::Priority::Medium) {
                             Ok(story_id) => {
+                                app.undo.record(format!("create #{}", story_id), Op::Delete { id: story_id });
                                 // Open editor for body
                                 match editor::spawn_editor("") {
                                     Ok(Some(body)) if !body.trim().is_empty() => {
-                                        if let Err(e) = db.update_story_description(story_id, &body) {
+                                        let patch = StoryPatch { description: Some(body), ..Default::default() };
+                                        if let Err(e) = db.update_story(story_id, &patch) {
                                             app.status_message = Some(format!("Error: {}", e));
                                         }
                                     }
@@ -277,21 +597,57 @@ fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Te
                         refresh_board(db, app);
                     }
                     Mode::Input(InputTarget::EditStoryTitle) => {
-                        if let Some(story) = &app.current_story {
-                            if let Err(e) = db.update_story_title(story.id, &text) {
+                        if let Some(id) = app.current_story.as_ref().map(|s| s.id) {
+                            let op = Op::Update { id, patch: StoryPatch { title: Some(text), ..Default::default() } };
+                            if let Err(e) = app.undo.perform(db, format!("rename #{}", id), op) {
                                 app.status_message = Some(format!("Error: {}", e));
                             }
-                            if let Ok(s) = db.get_story(story.id) {
+                            if let Ok(s) = db.get_story(id) {
                                 app.current_story = Some(s);
                             }
                         }
                         app.mode = Mode::Detail;
                     }
+                    Mode::Input(InputTarget::NewTask) => {
+                        if let Some(story_id) = app.current_story.as_ref().map(|s| s.id) {
+                            let op = Op::CreateTask { story_id, title: text };
+                            perform(db, app, format!("add task to #{}", story_id), op);
+                            app.task_cursor = app.tasks.len().saturating_sub(1);
+                            app.detail_focus = DetailFocus::Tasks;
+                        }
+                        app.mode = Mode::Detail;
+                    }
+                    Mode::Input(InputTarget::NewEpic) => {
+                        match db.create_epic(&text, "", "white") {
+                            Ok(id) => {
+                                app.undo.record(format!("create epic {}", text), Op::DeleteEpic { id });
+                                refresh_board(db, app);
+                                if let Some(i) = app.epics.iter().position(|e| e.id == id) {
+                                    app.list_selection = i + 1;
+                                }
+                            }
+                            Err(e) => app.status_message = Some(format!("Error: {}", e)),
+                        }
+                        app.mode = Mode::EpicList;
+                    }
+                    Mode::Input(InputTarget::RenameEpic) => {
+                        if let Some(id) = app.selected_epic().map(|e| e.id) {
+                            let op = Op::UpdateEpic { id, title: Some(text), color: None };
+                            perform(db, app, "rename epic", op);
+                        }
+                        app.mode = Mode::EpicList;
+                    }
+                    Mode::Input(InputTarget::RenameTask) => {
+                        if let Some(id) = app.selected_task().map(|t| t.id) {
+                            perform(db, app, "rename task", Op::RenameTask { id, title: text });
+                        }
+                        app.mode = Mode::Detail;
+                    }
                     _ => { app.mode = Mode::Board; }
                 }
             } else {
                 app.mode = match app.mode {
-                    Mode::Input(InputTarget::EditStoryTitle) => Mode::Detail,
+                    Mode::Input(target) => target.origin(),
                     _ => Mode::Board,
                 };
             }
@@ -300,7 +656,8 @@ fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Te
         Action::InputCancel => {
             app.input_buffer.clear();
             app.mode = match app.mode {
-                Mode::Input(InputTarget::EditStoryTitle) => Mode::Detail,
+                Mode::Input(target) => target.origin(),
+                Mode::EpicPicker => picker_origin(app),
                 _ => Mode::Board,
             };
         }
@@ -312,33 +669,140 @@ fn handle_action(db: &Database, app: &mut App, action: Action, terminal: &mut Te
             }
         }
         Action::ConfirmYes => {
-            if let Mode::Confirm(ConfirmAction::DeleteStory) = app.mode {
-                if let Some(story) = app.selected_story() {
-                    let id = story.id;
-                    if let Err(e) = db.delete_story(id) {
-                        app.status_message = Some(format!("Error: {}", e));
+            if let Mode::Confirm(ConfirmAction::DeleteEpic) = app.mode {
+                if let Some((id, title)) = app.selected_epic().map(|e| (e.id, e.title.clone())) {
+                    match app.undo.perform(db, format!("delete epic {}", title), Op::DeleteEpic { id }) {
+                        Ok(_) => {
+                            if app.epic_filter == Some(id) {
+                                app.epic_filter = None;
+                            }
+                            app.list_selection = app.list_selection.saturating_sub(1);
+                        }
+                        Err(e) => app.status_message = Some(format!("Error: {}", e)),
                     }
                 }
+                app.mode = Mode::EpicList;
+                refresh_board(db, app);
+            }
+            if let Mode::Confirm(ConfirmAction::DeleteStory) = app.mode {
+                if let Some(id) = app.selected_story().map(|s| s.id)
+                    && let Err(e) = app.undo.perform(db, format!("delete #{}", id), Op::Delete { id })
+                {
+                    app.status_message = Some(format!("Error: {}", e));
+                }
                 app.mode = Mode::Board;
                 refresh_board(db, app);
             }
         }
         Action::ConfirmNo => {
-            app.mode = Mode::Board;
+            app.mode = match app.mode {
+                Mode::Confirm(action) => action.origin(),
+                _ => Mode::Board,
+            };
         }
     }
 }
 
+/// Move a story to another column, enforcing (strict) or warning about WIP limits.
+fn move_story(db: &Database, app: &mut App, id: i64, status: &Status) {
+    let description = format!("move #{} to {}", id, status.name(&app.workflow));
+    let patch = StoryPatch { status: Some(status.clone()), ..Default::default() };
+    app.status_message = match app.undo.perform(db, description, Op::Update { id, patch }) {
+        // Strict WIP limits are enforced by the update itself; otherwise only warn
+        Ok(()) => match db.wip_violation(id, status) {
+            Ok(violation) => violation.map(|v| format!("Warning: {}", v)),
+            Err(e) => Some(format!("Error: {}", e)),
+        },
+        Err(StackError::Conflict(violation)) => Some(format!("Refused: {}", violation)),
+        Err(e) => Some(format!("Error: {}", e)),
+    };
+    refresh_board(db, app);
+}
+
+/// The view the epic picker was opened from.
+fn picker_origin(app: &App) -> Mode {
+    if app.current_story.is_some() { Mode::Detail } else { Mode::Board }
+}
+
+/// Apply an undoable change and reload the board.
+fn perform(db: &Database, app: &mut App, description: impl Into<String>, op: Op) {
+    if let Err(e) = app.undo.perform(db, description, op) {
+        app.status_message = Some(format!("Error: {}", e));
+    }
+    refresh_board(db, app);
+}
+
+/// Report the outcome of an undo or redo and reload whatever it touched.
+fn after_undo(db: &Database, app: &mut App, result: Option<Result<String, StackError>>, verb: &str, empty: &str) {
+    app.status_message = Some(match result {
+        Some(Ok(description)) => format!("{}: {}", verb, description),
+        Some(Err(e)) => format!("Error: {}", e),
+        None => empty.to_string(),
+    });
+    if app.epic_filter.is_some_and(|id| db.get_epic(id).is_err()) {
+        app.epic_filter = None;
+    }
+    if let Some(id) = app.current_story.as_ref().map(|s| s.id) {
+        match db.get_story(id) {
+            Ok(story) => app.current_story = Some(story),
+            Err(_) => {
+                // The open story was deleted again
+                app.current_story = None;
+                app.status_history.clear();
+                app.tasks.clear();
+                app.mode = Mode::Board;
+            }
+        }
+    }
+    refresh_board(db, app);
+}
+
 fn refresh_board(db: &Database, app: &mut App) {
-    for (i, status) in Status::all().iter().enumerate() {
-        match db.list_stories_by_status(*status, app.epic_filter) {
-            Ok(stories) => app.columns[i] = stories,
-            Err(e) => app.status_message = Some(format!("Error loading {}: {}", status, e)),
+    match db.list_boards() {
+        Ok(boards) => app.boards = boards,
+        Err(e) => app.status_message = Some(format!("Error loading boards: {}", e)),
+    }
+    app.board_id = db.board_id();
+    app.search_hits = match &app.search_query {
+        Some(query) => match db.search_stories(query) {
+            Ok(hits) => hits.into_iter().map(|h| h.story.id).collect(),
+            Err(e) => {
+                app.status_message = Some(format!("Search error: {}", e));
+                vec![]
+            }
+        },
+        None => vec![],
+    };
+    match db.list_columns() {
+        Ok(workflow) => app.workflow = workflow,
+        Err(e) => app.status_message = Some(format!("Error loading columns: {}", e)),
+    }
+    app.columns.resize(app.workflow.len(), vec![]);
+    for (i, column) in app.workflow.iter().enumerate() {
+        match db.list_stories_by_status(&column.key, app.epic_filter, &app.filter, column.sort) {
+            Ok(mut stories) => {
+                if app.search_query.is_some() {
+                    stories.retain(|s| app.search_hits.contains(&s.id));
+                }
+                app.columns[i] = stories;
+            }
+            Err(e) => app.status_message = Some(format!("Error loading {}: {}", column.name, e)),
         }
     }
     match db.list_epics() {
         Ok(epics) => app.epics = epics,
         Err(e) => app.status_message = Some(format!("Error loading epics: {}", e)),
     }
+    if let Some(id) = app.current_story.as_ref().map(|s| s.id) {
+        if let Ok(story) = db.get_story(id) {
+            app.current_story = Some(story);
+        }
+        if let Ok(history) = db.story_history(id) {
+            app.status_history = history;
+        }
+        if let Ok(tasks) = db.list_tasks(id) {
+            app.tasks = tasks;
+        }
+    }
     app.clamp_selections();
 }
@@ -2,68 +2,191 @@ use std::fmt;
 use std::str::FromStr;
 use serde::Serialize;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
-#[serde(rename_all = "snake_case")]
-pub enum Status {
-    ToDo,
-    InProgress,
-    InReview,
-    Done,
-}
+/// Key of the workflow column a story is in, e.g. `todo` or `in_progress`.
+/// The columns themselves (name, order, done flag) live in the database.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Status(String);
 
 impl Status {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Status::ToDo => "To Do",
-            Status::InProgress => "In Progress",
-            Status::InReview => "In Review",
-            Status::Done => "Done",
-        }
+    /// Wrap a key as stored in the database, without normalizing it.
+    pub fn new(key: &str) -> Status {
+        Status(key.to_string())
     }
 
-    pub fn all() -> &'static [Status] {
-        &[Status::ToDo, Status::InProgress, Status::InReview, Status::Done]
+    pub fn key(&self) -> &str {
+        &self.0
     }
 
-    pub fn next(&self) -> Option<Status> {
-        match self {
-            Status::ToDo => Some(Status::InProgress),
-            Status::InProgress => Some(Status::InReview),
-            Status::InReview => Some(Status::Done),
-            Status::Done => None,
-        }
+    /// Display name of this status in the given workflow, falling back to the key.
+    pub fn name<'a>(&'a self, columns: &'a [Column]) -> &'a str {
+        columns.iter()
+            .find(|c| c.key == *self)
+            .map(|c| c.name.as_str())
+            .unwrap_or(&self.0)
     }
 
-    pub fn prev(&self) -> Option<Status> {
-        match self {
-            Status::ToDo => None,
-            Status::InProgress => Some(Status::ToDo),
-            Status::InReview => Some(Status::InProgress),
-            Status::Done => Some(Status::InReview),
-        }
+    pub fn next(&self, columns: &[Column]) -> Option<Status> {
+        let i = columns.iter().position(|c| c.key == *self)?;
+        columns.get(i + 1).map(|c| c.key.clone())
+    }
+
+    pub fn prev(&self, columns: &[Column]) -> Option<Status> {
+        let i = columns.iter().position(|c| c.key == *self)?;
+        i.checked_sub(1).map(|i| columns[i].key.clone())
+    }
+
+    /// Whether moving a story from this status to `to` starts work on it:
+    /// `to` is a started column and this one is not.
+    pub fn starts_work(&self, to: &Status, columns: &[Column]) -> bool {
+        let started = |key: &Status| columns.iter().any(|c| c.key == *key && c.started);
+        started(to) && !started(self)
     }
 }
 
 impl fmt::Display for Status {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.as_str())
+        write!(f, "{}", self.0)
     }
 }
 
 impl FromStr for Status {
     type Err = String;
 
+    /// Normalizes `In-Progress`, `in progress` etc. to the `in_progress` key form.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "todo" | "to_do" | "to-do" => Ok(Status::ToDo),
-            "in-progress" | "in_progress" => Ok(Status::InProgress),
-            "in-review" | "in_review" => Ok(Status::InReview),
-            "done" => Ok(Status::Done),
-            _ => Err(format!("unknown status: '{}' (expected: todo, in-progress, in-review, done)", s)),
+        let key = s.trim().to_lowercase().replace(['-', ' '], "_");
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid status: '{}' (expected a column key such as todo, in-progress, done)", s));
+        }
+        Ok(match key.as_str() {
+            "to_do" => Status::new("todo"),
+            _ => Status(key),
+        })
+    }
+}
+
+/// Story field a listing is ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortField {
+    /// Manual order, set by moving cards on the board.
+    #[default]
+    Rank,
+    Id,
+    Priority,
+    Created,
+    Updated,
+    Title,
+}
+
+impl SortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortField::Rank => "rank",
+            SortField::Id => "id",
+            SortField::Priority => "priority",
+            SortField::Created => "created",
+            SortField::Updated => "updated",
+            SortField::Title => "title",
         }
     }
 }
 
+/// Order of a story listing, written `priority` or `priority:desc`.
+/// Ties are broken by id, in the same direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sort {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+/// Orders `o` cycles a board column through.
+pub const COLUMN_SORTS: &[Sort] = &[
+    Sort { field: SortField::Rank, descending: false },
+    Sort { field: SortField::Priority, descending: true },
+    Sort { field: SortField::Updated, descending: true },
+    Sort { field: SortField::Created, descending: false },
+    Sort { field: SortField::Title, descending: false },
+];
+
+impl Sort {
+    /// The order after this one in `COLUMN_SORTS`.
+    pub fn next(self) -> Sort {
+        let current = COLUMN_SORTS.iter().position(|s| *s == self);
+        COLUMN_SORTS[current.map_or(0, |i| (i + 1) % COLUMN_SORTS.len())]
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.field.as_str(), if self.descending { ":desc" } else { "" })
+    }
+}
+
+impl FromStr for Sort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, direction) = s.split_once(':').unwrap_or((s, "asc"));
+        let field = match field {
+            "rank" => SortField::Rank,
+            "id" => SortField::Id,
+            "priority" => SortField::Priority,
+            "created" => SortField::Created,
+            "updated" => SortField::Updated,
+            "title" => SortField::Title,
+            _ => return Err(format!("unknown sort field: '{}' (expected: rank, id, priority, created, updated, title)", field)),
+        };
+        let descending = match direction {
+            "asc" => false,
+            "desc" => true,
+            _ => return Err(format!("unknown sort direction: '{}' (expected: asc, desc)", direction)),
+        };
+        Ok(Sort { field, descending })
+    }
+}
+
+impl Serialize for Sort {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Which slice of a story listing to return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Page {
+    pub limit: Option<usize>,
+    pub offset: usize,
+    /// Continue after this story, in the listing's order (keyset pagination).
+    pub after: Option<i64>,
+}
+
+/// A column of the board's workflow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Column {
+    pub id: i64,
+    pub key: Status,
+    pub name: String,
+    pub position: i64,
+    /// Stories in a done column count as finished (e.g. they no longer block others).
+    pub done: bool,
+    /// Maximum number of stories the column should hold, if limited.
+    pub wip_limit: Option<u32>,
+    /// Stories currently in the column, across all epics.
+    pub story_count: i64,
+    /// Order of the column's cards on the board.
+    pub sort: Sort,
+    /// Stories in a started column are being worked on; a blocked story
+    /// cannot move into one from a column that isn't.
+    pub started: bool,
+}
+
+impl Column {
+    pub fn over_limit(&self) -> bool {
+        self.wip_limit.is_some_and(|limit| self.story_count > limit as i64)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Priority {
@@ -82,6 +205,24 @@ impl Priority {
             Priority::Critical => "Critical",
         }
     }
+
+    /// One step more urgent, staying at `Critical`.
+    pub fn raised(self) -> Priority {
+        match self {
+            Priority::Low => Priority::Medium,
+            Priority::Medium => Priority::High,
+            Priority::High | Priority::Critical => Priority::Critical,
+        }
+    }
+
+    /// One step less urgent, staying at `Low`.
+    pub fn lowered(self) -> Priority {
+        match self {
+            Priority::Critical => Priority::High,
+            Priority::High => Priority::Medium,
+            Priority::Medium | Priority::Low => Priority::Low,
+        }
+    }
 }
 
 impl fmt::Display for Priority {
@@ -104,6 +245,14 @@ impl FromStr for Priority {
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct Board {
+    pub id: i64,
+    pub name: String,
+    pub created_at: String,
+    pub story_count: i64,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize)]
 pub struct Epic {
@@ -111,6 +260,25 @@ pub struct Epic {
     pub title: String,
     pub description: String,
     pub color: String,
+    pub story_count: i64,
+    /// Stories of this epic sitting in a done column.
+    pub done_count: i64,
+}
+
+/// Colors `c` cycles an epic through in the TUI.
+pub const EPIC_COLORS: &[&str] = &["white", "red", "green", "yellow", "blue", "magenta", "cyan"];
+
+impl Epic {
+    /// Share of the epic's stories that are done, rounded down; 0 when it has none.
+    pub fn percent_complete(&self) -> i64 {
+        if self.story_count == 0 { 0 } else { self.done_count * 100 / self.story_count }
+    }
+
+    /// The color after this epic's in `EPIC_COLORS`.
+    pub fn next_color(&self) -> &'static str {
+        let current = EPIC_COLORS.iter().position(|c| c.eq_ignore_ascii_case(&self.color));
+        EPIC_COLORS[current.map_or(0, |i| (i + 1) % EPIC_COLORS.len())]
+    }
 }
 
 #[allow(dead_code)]
@@ -124,6 +292,72 @@ pub struct Story {
     pub priority: Priority,
     pub created_at: String,
     pub updated_at: String,
+    /// Position on the board; cards sorted by rank keep it across columns.
+    pub rank: f64,
+    pub labels: Vec<String>,
+    /// True while any story blocking this one is not done.
+    pub blocked: bool,
+    /// Ids of the unfinished stories blocking this one.
+    pub blocked_by: Vec<i64>,
+    pub task_count: i64,
+    pub tasks_done: i64,
+    /// The live claim on this story, if an agent holds one.
+    pub claim: Option<Claim>,
+}
+
+/// An agent's lease on a story, released automatically once it expires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Claim {
+    pub owner: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Label {
+    pub id: i64,
+    pub name: String,
+    pub story_count: i64,
+}
+
+/// Trim and validate a label name for use on the command line.
+pub fn parse_label(s: &str) -> Result<String, String> {
+    let name = s.trim();
+    if name.is_empty() {
+        Err("label cannot be empty".to_string())
+    } else if name.contains(',') {
+        Err(format!("label cannot contain ',': '{}'", name))
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+/// Parse how long a claim lasts, e.g. `30m` or `2h`, into seconds.
+pub fn parse_ttl(s: &str) -> Result<i64, String> {
+    match parse_age(s.trim()) {
+        Ok(seconds) if seconds > 0 => Ok(seconds),
+        _ => Err(format!("invalid ttl '{}' (expected a number followed by m, h, d or w, e.g. 30m)", s)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub story: Story,
+    /// Matching excerpt with hits wrapped in `[` `]`.
+    pub snippet: String,
+    /// FTS5 bm25 rank; lower is a better match.
+    pub rank: f64,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusChange {
+    pub id: i64,
+    pub story_id: i64,
+    pub from_status: Option<Status>,
+    pub to_status: Status,
+    pub changed_at: String,
+    pub duration_secs: i64,
 }
 
 #[allow(dead_code)]
@@ -136,21 +370,275 @@ pub struct Task {
     pub sort_order: i64,
 }
 
+/// Fields to change on a story; `None` leaves a field as it is.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StoryPatch {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<Status>,
+    pub priority: Option<Priority>,
+    /// `Some(None)` detaches the story from its epic.
+    pub epic_id: Option<Option<i64>>,
+}
+
+/// A story together with everything deleting it removes, so it can be restored.
+#[derive(Debug, Clone)]
+pub struct StorySnapshot {
+    pub board_id: i64,
+    pub story: Story,
+    pub tasks: Vec<Task>,
+    pub history: Vec<StatusChange>,
+    /// Stories this one blocks, and stories blocking it (finished or not).
+    pub blocks: Vec<i64>,
+    pub blockers: Vec<i64>,
+}
+
+/// An epic and the stories it held, so deleting it can be undone.
+#[derive(Debug, Clone)]
+pub struct EpicSnapshot {
+    pub board_id: i64,
+    pub epic: Epic,
+    pub stories: Vec<i64>,
+}
+
+/// How a `--where` term compares a story's field with its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+}
+
+/// One condition of a story filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// In any of these columns.
+    Status(Vec<Status>),
+    /// Any of these priorities.
+    Priority(Vec<Priority>),
+    PriorityCompare(Comparison, Priority),
+    /// In any of these epics; an empty list means no epic.
+    Epic(Vec<i64>),
+    /// Carrying any of these labels.
+    Label(Vec<String>),
+    /// Title contains the text, ignoring case.
+    Title(String),
+    /// Description contains the text, ignoring case.
+    Body(String),
+    /// Time since the last update compared with a number of seconds, so
+    /// `updated<7d` means "updated within the last week".
+    Updated(Comparison, i64),
+    Created(Comparison, i64),
+    Blocked(bool),
+}
+
+/// A story filter such as `priority>=high status:todo,in-progress updated<7d`:
+/// whitespace-separated terms that must all match.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    pub terms: Vec<Term>,
+}
+
+impl Filter {
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+/// Age units accepted by `updated` and `created`, in seconds, largest first.
+const AGE_UNITS: &[(char, i64)] = &[('w', 604_800), ('d', 86_400), ('h', 3_600), ('m', 60)];
+
+fn parse_age(value: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid age '{}' (expected a number followed by m, h, d or w, e.g. 7d)", value);
+    let unit = value.chars().last().ok_or_else(invalid)?;
+    let seconds = AGE_UNITS.iter().find(|(u, _)| *u == unit).map(|(_, s)| *s).ok_or_else(invalid)?;
+    if value.starts_with('-') {
+        return Err(invalid());
+    }
+    let amount: i64 = value[..value.len() - 1].parse().map_err(|_| invalid())?;
+    amount.checked_mul(seconds).ok_or_else(invalid)
+}
+
+fn format_age(seconds: i64) -> String {
+    let (unit, size) = AGE_UNITS.iter()
+        .find(|(_, size)| seconds % size == 0)
+        .unwrap_or(&('m', 60));
+    format!("{}{}", seconds / size, unit)
+}
+
+/// Split on whitespace outside double quotes, dropping the quotes.
+fn split_terms(s: &str) -> Result<Vec<String>, String> {
+    let mut terms = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in s.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote in filter".to_string());
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    Ok(terms)
+}
+
+impl FromStr for Term {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let field_end = s.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(s.len());
+        let (field, rest) = s.split_at(field_end);
+        let op = ["<=", ">=", "<", ">", ":", "=", "~"].into_iter()
+            .find(|op| rest.starts_with(op))
+            .ok_or_else(|| format!("invalid filter term '{}' (expected field:value, e.g. status:todo)", s))?;
+        let value = &rest[op.len()..];
+        if value.is_empty() {
+            return Err(format!("missing value in filter term '{}'", s));
+        }
+        let comparison = match op {
+            "<" => Some(Comparison::Lt),
+            "<=" => Some(Comparison::Le),
+            ">" => Some(Comparison::Gt),
+            ">=" => Some(Comparison::Ge),
+            _ => None,
+        };
+        let list = || value.split(',').map(str::trim).filter(|v| !v.is_empty());
+        let unsupported = || format!("'{}' cannot be used with {}", op, field);
+
+        Ok(match (field, op, comparison) {
+            ("status", ":" | "=", _) => Term::Status(list().map(str::parse).collect::<Result<_, _>>()?),
+            ("priority", ":" | "=", _) => Term::Priority(list().map(str::parse).collect::<Result<_, _>>()?),
+            ("priority", _, Some(comparison)) => Term::PriorityCompare(comparison, value.parse()?),
+            ("epic", ":" | "=", _) if value == "none" => Term::Epic(vec![]),
+            ("epic", ":" | "=", _) => Term::Epic(list()
+                .map(|id| id.parse().map_err(|_| format!("invalid epic id '{}'", id)))
+                .collect::<Result<_, _>>()?),
+            ("label", ":" | "=", _) => Term::Label(list().map(parse_label).collect::<Result<_, _>>()?),
+            ("title", "~", _) => Term::Title(value.to_string()),
+            ("body", "~", _) => Term::Body(value.to_string()),
+            ("updated", _, Some(comparison)) => Term::Updated(comparison, parse_age(value)?),
+            ("created", _, Some(comparison)) => Term::Created(comparison, parse_age(value)?),
+            ("blocked", ":" | "=", _) => Term::Blocked(match value {
+                "true" | "yes" => true,
+                "false" | "no" => false,
+                _ => return Err(format!("invalid value for blocked: '{}' (expected true or false)", value)),
+            }),
+            ("status" | "priority" | "epic" | "label" | "title" | "body" | "updated" | "created" | "blocked", _, _) => {
+                return Err(unsupported());
+            }
+            _ => return Err(format!(
+                "unknown filter field '{}' (expected status, priority, epic, label, title, body, updated, created or blocked)",
+                field,
+            )),
+        })
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let terms = split_terms(s)?.iter().map(|t| t.parse()).collect::<Result<_, _>>()?;
+        Ok(Filter { terms })
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |items: Vec<String>| items.join(",");
+        let text = |t: &str| if t.contains(char::is_whitespace) { format!("\"{}\"", t) } else { t.to_string() };
+        match self {
+            Term::Status(statuses) => write!(f, "status:{}", join(statuses.iter().map(|s| s.to_string()).collect())),
+            Term::Priority(priorities) => write!(f, "priority:{}", join(priorities.iter().map(|p| p.as_str().to_lowercase()).collect())),
+            Term::PriorityCompare(op, p) => write!(f, "priority{}{}", op.as_str(), p.as_str().to_lowercase()),
+            Term::Epic(ids) if ids.is_empty() => write!(f, "epic:none"),
+            Term::Epic(ids) => write!(f, "epic:{}", join(ids.iter().map(|id| id.to_string()).collect())),
+            Term::Label(labels) => write!(f, "label:{}", join(labels.clone())),
+            Term::Title(t) => write!(f, "title~{}", text(t)),
+            Term::Body(t) => write!(f, "body~{}", text(t)),
+            Term::Updated(op, age) => write!(f, "updated{}{}", op.as_str(), format_age(*age)),
+            Term::Created(op, age) => write!(f, "created{}{}", op.as_str(), format_age(*age)),
+            Term::Blocked(blocked) => write!(f, "blocked:{}", blocked),
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self.terms.iter().map(|t| t.to_string()).collect();
+        write!(f, "{}", terms.join(" "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn workflow() -> Vec<Column> {
+        ["todo", "in_progress", "done"].iter().enumerate()
+            .map(|(i, key)| Column {
+                id: i as i64 + 1,
+                key: Status::new(key),
+                name: key.to_uppercase(),
+                position: i as i64,
+                done: *key == "done",
+                wip_limit: None,
+                story_count: 0,
+                sort: Sort::default(),
+                started: *key == "in_progress",
+            })
+            .collect()
+    }
+
     #[test]
-    fn status_display_and_variants() {
-        assert_eq!(Status::ToDo.as_str(), "To Do");
-        assert_eq!(Status::InProgress.as_str(), "In Progress");
-        assert_eq!(Status::InReview.as_str(), "In Review");
-        assert_eq!(Status::Done.as_str(), "Done");
+    fn status_next_and_prev_follow_workflow() {
+        let columns = workflow();
+        let todo = Status::new("todo");
+        let done = Status::new("done");
+        assert_eq!(todo.next(&columns), Some(Status::new("in_progress")));
+        assert_eq!(todo.prev(&columns), None);
+        assert_eq!(done.prev(&columns), Some(Status::new("in_progress")));
+        assert_eq!(done.next(&columns), None);
+        assert_eq!(Status::new("gone").next(&columns), None);
     }
 
     #[test]
-    fn status_all_returns_four_columns() {
-        assert_eq!(Status::all().len(), 4);
+    fn only_entering_a_started_column_starts_work() {
+        let columns = workflow();
+        let (todo, doing, done) = (Status::new("todo"), Status::new("in_progress"), Status::new("done"));
+        assert!(todo.starts_work(&doing, &columns));
+        assert!(done.starts_work(&doing, &columns));
+        assert!(!doing.starts_work(&done, &columns));
+        assert!(!doing.starts_work(&doing, &columns));
+        assert!(!done.starts_work(&todo, &columns));
+    }
+
+    #[test]
+    fn status_name_falls_back_to_key() {
+        let columns = workflow();
+        assert_eq!(Status::new("in_progress").name(&columns), "IN_PROGRESS");
+        assert_eq!(Status::new("qa").name(&columns), "qa");
     }
 
     #[test]
@@ -160,6 +648,14 @@ mod tests {
         assert!(Priority::Medium > Priority::Low);
     }
 
+    #[test]
+    fn priority_raise_and_lower_saturate() {
+        assert_eq!(Priority::Low.raised(), Priority::Medium);
+        assert_eq!(Priority::Critical.raised(), Priority::Critical);
+        assert_eq!(Priority::High.lowered(), Priority::Medium);
+        assert_eq!(Priority::Low.lowered(), Priority::Low);
+    }
+
     #[test]
     fn priority_display() {
         assert_eq!(Priority::Low.as_str(), "Low");
@@ -168,11 +664,20 @@ mod tests {
 
     #[test]
     fn status_from_str() {
-        assert_eq!("todo".parse::<Status>().unwrap(), Status::ToDo);
-        assert_eq!("in-progress".parse::<Status>().unwrap(), Status::InProgress);
-        assert_eq!("in-review".parse::<Status>().unwrap(), Status::InReview);
-        assert_eq!("done".parse::<Status>().unwrap(), Status::Done);
-        assert!("invalid".parse::<Status>().is_err());
+        assert_eq!("todo".parse::<Status>().unwrap(), Status::new("todo"));
+        assert_eq!("to-do".parse::<Status>().unwrap(), Status::new("todo"));
+        assert_eq!("in-progress".parse::<Status>().unwrap(), Status::new("in_progress"));
+        assert_eq!("In Review".parse::<Status>().unwrap(), Status::new("in_review"));
+        assert_eq!("QA".parse::<Status>().unwrap(), Status::new("qa"));
+        assert!("".parse::<Status>().is_err());
+        assert!("in/valid".parse::<Status>().is_err());
+    }
+
+    #[test]
+    fn parse_label_trims_and_validates() {
+        assert_eq!(parse_label("  backend ").unwrap(), "backend");
+        assert!(parse_label("   ").is_err());
+        assert!(parse_label("a,b").is_err());
     }
 
     #[test]
@@ -183,4 +688,63 @@ mod tests {
         assert_eq!("critical".parse::<Priority>().unwrap(), Priority::Critical);
         assert!("invalid".parse::<Priority>().is_err());
     }
+
+    #[test]
+    fn filter_parses_each_field() {
+        let filter: Filter = "priority>=high status:todo,in-progress epic:3 updated<7d title~login".parse().unwrap();
+        assert_eq!(filter.terms, vec![
+            Term::PriorityCompare(Comparison::Ge, Priority::High),
+            Term::Status(vec![Status::new("todo"), Status::new("in_progress")]),
+            Term::Epic(vec![3]),
+            Term::Updated(Comparison::Lt, 7 * 86_400),
+            Term::Title("login".to_string()),
+        ]);
+        let filter: Filter = r#"epic:none label:a,b blocked:no body~"two words" created>=90m"#.parse().unwrap();
+        assert_eq!(filter.terms, vec![
+            Term::Epic(vec![]),
+            Term::Label(vec!["a".to_string(), "b".to_string()]),
+            Term::Blocked(false),
+            Term::Body("two words".to_string()),
+            Term::Created(Comparison::Ge, 90 * 60),
+        ]);
+        assert!("".parse::<Filter>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn filter_rejects_bad_terms() {
+        let bad_terms = [
+            "colour:red", "status", "status:", "title:login", "priority>=urgent", "updated<7y", "epic>3", "title~\"open",
+            "updated<-7d", "updated<9999999999999999w",
+        ];
+        for bad in bad_terms {
+            assert!(bad.parse::<Filter>().is_err(), "{} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn filter_display_round_trips() {
+        let text = r#"priority:high,critical priority<medium epic:none title~"log in" updated>2w created<36h blocked:true"#;
+        let filter: Filter = text.parse().unwrap();
+        assert_eq!(filter.to_string(), text);
+    }
+
+    #[test]
+    fn sort_parses_and_cycles() {
+        assert_eq!("priority:desc".parse::<Sort>().unwrap(), Sort { field: SortField::Priority, descending: true });
+        assert_eq!("title".parse::<Sort>().unwrap(), Sort { field: SortField::Title, descending: false });
+        assert_eq!("rank".parse::<Sort>().unwrap(), Sort::default());
+        assert!("title:up".parse::<Sort>().is_err());
+        assert!("size".parse::<Sort>().is_err());
+        assert_eq!(Sort::default().next().to_string(), "priority:desc");
+        assert_eq!(COLUMN_SORTS[COLUMN_SORTS.len() - 1].next(), Sort::default());
+    }
+
+    #[test]
+    fn ttl_must_be_positive() {
+        assert_eq!(parse_ttl("30m"), Ok(1800));
+        assert_eq!(parse_ttl("2h"), Ok(7200));
+        for bad in ["0m", "-5m", "30", "soon", "9999999999999999w"] {
+            assert!(parse_ttl(bad).is_err(), "{} should not parse", bad);
+        }
+    }
 }
@@ -0,0 +1,404 @@
+use std::io::IsTerminal;
+use crossterm::style::{Attribute, ContentStyle};
+use ratatui::backend::IntoCrossterm;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use serde_json::Value;
+
+use crate::cli::{Command, StoryAction};
+use crate::cli_handler::Reply;
+use crate::db::Database;
+use crate::error::StackError;
+use crate::models::Column;
+
+/// Widest a board column gets before its cards are cut short.
+const BOARD_COLUMN_WIDTH: usize = 32;
+
+/// How CLI results are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Compact JSON, one object per command
+    Json,
+    /// Indented JSON
+    Pretty,
+    /// Aligned tables and rendered pages
+    Table,
+    /// Tab-separated rows without headers or styling
+    Plain,
+}
+
+impl Format {
+    /// Tables for a person at a terminal, JSON when piped.
+    pub fn detect() -> Format {
+        if std::io::stdout().is_terminal() { Format::Table } else { Format::Json }
+    }
+}
+
+/// The shape of a command's result, for the human-readable formats.
+pub enum View {
+    /// `stack board`, with the workflow columns in order.
+    Board(Vec<Column>),
+    /// A list of stories.
+    Stories,
+    /// A single story.
+    Story,
+    Other,
+}
+
+impl View {
+    pub fn of(command: &Command, db: &Database) -> Result<View, StackError> {
+        Ok(match command {
+            Command::Board { action: None, .. } => View::Board(db.list_columns()?),
+            Command::Story { action: StoryAction::List { .. } | StoryAction::Search { .. } } => View::Stories,
+            Command::Story {
+                action: StoryAction::Get { .. } | StoryAction::Next { .. } | StoryAction::Claim { .. }
+                    | StoryAction::Renew { .. } | StoryAction::Release { .. },
+            } => View::Story,
+            _ => View::Other,
+        })
+    }
+}
+
+/// Format a successful result.
+pub fn render(view: &View, reply: &Reply, format: Format) -> String {
+    let value = &reply.result;
+    let mut envelope = serde_json::json!({ "result": value });
+    if let Some(cursor) = &reply.next_cursor {
+        envelope["next_cursor"] = cursor.as_str().into();
+    }
+    let mut lines = match format {
+        Format::Json => return envelope.to_string(),
+        Format::Pretty => return pretty(&envelope),
+        Format::Table => match view {
+            View::Board(columns) => board_table(columns, value),
+            View::Stories => table(&story_rows(value)),
+            View::Story => story_page(value),
+            View::Other => generic(value, true),
+        },
+        Format::Plain => match view {
+            View::Board(columns) => board_rows(columns, value).iter().map(|row| Line::from(row.join("\t"))).collect(),
+            View::Stories => story_rows(value).iter().skip(1).map(|row| Line::from(row.join("\t"))).collect(),
+            View::Story => story_page(value),
+            View::Other => generic(value, false),
+        },
+    };
+    if let Some(cursor) = reply.next_cursor.as_ref().filter(|_| format == Format::Table) {
+        lines.push(Line::styled(format!("More: --cursor {}", cursor), Style::default().fg(Color::DarkGray)));
+    }
+    let styled = format == Format::Table && std::io::stdout().is_terminal();
+    lines.iter()
+        .map(|line| if styled { ansi(line) } else { line.to_string() })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Format an error for stderr.
+pub fn render_error(error: &StackError, format: Format) -> String {
+    match format {
+        Format::Json => serde_json::json!({ "error": error.to_json() }).to_string(),
+        Format::Pretty => pretty(&serde_json::json!({ "error": error.to_json() })),
+        Format::Table | Format::Plain => format!("error: {}", error),
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// A JSON value as a table cell: strings unquoted, arrays comma-separated.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(cell).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        s.to_string()
+    } else {
+        let cut: String = s.chars().take(width.saturating_sub(1)).collect();
+        format!("{}…", cut)
+    }
+}
+
+/// Align `rows` into columns; the first row is the header.
+fn table(rows: &[Vec<String>]) -> Vec<Line<'static>> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| rows.iter().filter_map(|row| row.get(i)).map(|c| c.chars().count()).max().unwrap_or(0))
+        .collect();
+    rows.iter().enumerate().map(|(i, row)| {
+        let text = row.iter().zip(&widths)
+            .map(|(c, &w)| format!("{:<w$}", c))
+            .collect::<Vec<_>>()
+            .join("  ");
+        let text = text.trim_end().to_string();
+        if i == 0 { Line::styled(text, Style::default().add_modifier(Modifier::BOLD)) } else { Line::from(text) }
+    }).collect()
+}
+
+/// Header plus one row per story.
+fn story_rows(value: &Value) -> Vec<Vec<String>> {
+    let header = ["ID", "PRIORITY", "STATUS", "TITLE"].map(String::from).to_vec();
+    let stories = value.as_array().map(Vec::as_slice).unwrap_or_default();
+    std::iter::once(header)
+        .chain(stories.iter().map(|s| vec![
+            cell(&s["id"]),
+            cell(&s["priority"]),
+            cell(&s["status"]),
+            cell(&s["title"]),
+        ]))
+        .collect()
+}
+
+/// The stories `stack board` lists under `column`.
+fn column_stories<'a>(value: &'a Value, column: &Column) -> &'a [Value] {
+    value.as_array().into_iter().flatten()
+        .find(|c| c["key"] == column.key.key())
+        .and_then(|c| c["stories"].as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// One `column, id, priority, title` row per story, in workflow order.
+fn board_rows(columns: &[Column], value: &Value) -> Vec<Vec<String>> {
+    columns.iter()
+        .flat_map(|column| {
+            column_stories(value, column).iter().map(move |s| vec![
+                column.key.key().to_string(),
+                cell(&s["id"]),
+                cell(&s["priority"]),
+                cell(&s["title"]),
+            ])
+        })
+        .collect()
+}
+
+/// The workflow columns side by side, one `#id title` card per line.
+fn board_table(columns: &[Column], value: &Value) -> Vec<Line<'static>> {
+    let cards: Vec<Vec<String>> = columns.iter()
+        .map(|column| {
+            column_stories(value, column).iter().map(|s| format!("#{} {}", cell(&s["id"]), cell(&s["title"]))).collect()
+        })
+        .collect();
+    let headers: Vec<String> = columns.iter().zip(&cards)
+        .map(|(column, cards)| match column.wip_limit {
+            Some(limit) => format!("{} ({}/{})", column.name, cards.len(), limit),
+            None => format!("{} ({})", column.name, cards.len()),
+        })
+        .collect();
+    let widths: Vec<usize> = headers.iter().zip(&cards)
+        .map(|(header, cards)| {
+            let widest = cards.iter().map(|c| c.chars().count()).chain([header.chars().count()]).max().unwrap_or(0);
+            widest.min(BOARD_COLUMN_WIDTH)
+        })
+        .collect();
+    let row = |cells: Vec<String>| -> String {
+        cells.iter().zip(&widths)
+            .map(|(c, &w)| format!("{:<w$}", truncate(c, w)))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![
+        Line::styled(row(headers), Style::default().add_modifier(Modifier::BOLD)),
+        Line::from(row(widths.iter().map(|&w| "-".repeat(w)).collect())),
+    ];
+    let height = cards.iter().map(Vec::len).max().unwrap_or(0);
+    for i in 0..height {
+        lines.push(Line::from(row(cards.iter().map(|c| c.get(i).cloned().unwrap_or_default()).collect())));
+    }
+    lines
+}
+
+/// A story's title, fields and markdown body.
+fn story_page(story: &Value) -> Vec<Line<'static>> {
+    // `story next` finds nothing when no story is ready
+    if story.is_null() {
+        return vec![Line::from("No matching story")];
+    }
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![
+        Line::styled(
+            format!("#{} {}", cell(&story["id"]), cell(&story["title"])),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::styled(
+            format!("Status: {}  Priority: {}", cell(&story["status"]), cell(&story["priority"])),
+            dim,
+        ),
+    ];
+    let mut field = |name: &str, value: String| {
+        if !value.is_empty() {
+            lines.push(Line::styled(format!("{}: {}", name, value), dim));
+        }
+    };
+    field("Epic", cell(&story["epic_id"]));
+    field("Labels", cell(&story["labels"]));
+    field("Blocked by", cell(&story["blocked_by"]));
+    if story["task_count"].as_i64().unwrap_or(0) > 0 {
+        field("Tasks", format!("{}/{}", cell(&story["tasks_done"]), cell(&story["task_count"])));
+    }
+    if story["claim"].is_object() {
+        field("Claimed by", format!("{} until {}", cell(&story["claim"]["owner"]), cell(&story["claim"]["expires_at"])));
+    }
+    let description = story["description"].as_str().unwrap_or_default();
+    if !description.is_empty() {
+        lines.push(Line::default());
+        lines.extend(tui_md::render(description));
+    }
+    lines
+}
+
+/// Lists of objects as tables, objects as `key: value` lines.
+fn generic(value: &Value, headers: bool) -> Vec<Line<'static>> {
+    match value {
+        Value::Array(items) if items.iter().all(Value::is_object) => {
+            let Some(Value::Object(first)) = items.first() else {
+                return vec![];
+            };
+            let keys: Vec<&String> = first.keys().collect();
+            let rows = items.iter().map(|item| keys.iter().map(|k| cell(&item[k.as_str()])).collect());
+            if headers {
+                let header = keys.iter().map(|k| k.to_uppercase()).collect();
+                table(&std::iter::once(header).chain(rows).collect::<Vec<_>>())
+            } else {
+                rows.map(|row: Vec<String>| Line::from(row.join("\t"))).collect()
+            }
+        }
+        Value::Object(fields) => fields.iter()
+            .map(|(key, value)| {
+                let text = match value {
+                    Value::Object(_) => value.to_string(),
+                    Value::Array(items) if items.iter().any(Value::is_object) => value.to_string(),
+                    _ => cell(value),
+                };
+                if headers { Line::from(format!("{}: {}", key, text)) } else { Line::from(format!("{}\t{}", key, text)) }
+            })
+            .collect(),
+        other => vec![Line::from(cell(other))],
+    }
+}
+
+/// The text of a rendered buffer, one line per row, with ANSI escapes if `styled`.
+pub fn buffer_text(buffer: &Buffer, styled: bool) -> String {
+    let width = buffer.area.width as usize;
+    buffer.content.chunks(width.max(1))
+        .map(|cells| {
+            let mut spans: Vec<Span> = vec![];
+            // Cells covered by the right half of a wide character are skipped
+            let mut skip = 0;
+            for cell in cells {
+                if skip == 0 {
+                    match spans.last_mut() {
+                        Some(span) if span.style == cell.style() => span.content.to_mut().push_str(cell.symbol()),
+                        _ => spans.push(Span::styled(cell.symbol().to_string(), cell.style())),
+                    }
+                }
+                skip = skip.max(Span::raw(cell.symbol()).width()).saturating_sub(1);
+            }
+            let line = Line::from(spans);
+            if styled { ansi(&line) } else { line.to_string().trim_end().to_string() }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A styled line as text with ANSI escapes.
+fn ansi(line: &Line) -> String {
+    line.spans.iter().map(|span: &Span| {
+        let style = line.style.patch(span.style);
+        let mut content = ContentStyle::new();
+        let color = |c: Option<Color>| c.filter(|&c| c != Color::Reset).map(IntoCrossterm::into_crossterm);
+        content.foreground_color = color(style.fg);
+        content.background_color = color(style.bg);
+        for (modifier, attribute) in [
+            (Modifier::BOLD, Attribute::Bold),
+            (Modifier::DIM, Attribute::Dim),
+            (Modifier::ITALIC, Attribute::Italic),
+            (Modifier::UNDERLINED, Attribute::Underlined),
+            (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+            (Modifier::REVERSED, Attribute::Reverse),
+        ] {
+            if style.add_modifier.contains(modifier) {
+                content.attributes.set(attribute);
+            }
+        }
+        content.apply(span.content.as_ref()).to_string()
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Sort, Status};
+
+    fn column(key: &str, name: &str) -> Column {
+        Column { id: 0, key: Status::new(key), name: name.to_string(), position: 0, done: false, wip_limit: None, story_count: 0, sort: Sort::default(), started: false }
+    }
+
+    #[test]
+    fn story_list_is_an_aligned_table() {
+        let stories = serde_json::json!([
+            { "id": 1, "priority": "high", "status": "todo", "title": "First" },
+            { "id": 12, "priority": "low", "status": "done", "title": "Second" },
+        ]);
+        let text = render(&View::Stories, &Reply::from(stories.clone()), Format::Table);
+        assert_eq!(text, "ID  PRIORITY  STATUS  TITLE\n1   high      todo    First\n12  low       done    Second");
+        let plain = render(&View::Stories, &Reply::from(stories.clone()), Format::Plain);
+        assert_eq!(plain, "1\thigh\ttodo\tFirst\n12\tlow\tdone\tSecond");
+    }
+
+    #[test]
+    fn board_puts_columns_side_by_side_in_workflow_order() {
+        let columns = [column("todo", "To Do"), column("done", "Done")];
+        let board = serde_json::json!([
+            { "key": "done", "name": "Done", "stories": [{ "id": 2, "title": "Shipped" }] },
+            { "key": "todo", "name": "To Do", "stories": [
+                { "id": 1, "title": "Plan" },
+                { "id": 3, "title": "A very long story title that will not fit" },
+            ]},
+        ]);
+        let lines: Vec<String> = render(&View::Board(columns.to_vec()), &Reply::from(board), Format::Table)
+            .lines().map(String::from).collect();
+        assert!(lines[0].starts_with("To Do (2)") && lines[0].ends_with("Done (1)"));
+        assert!(lines[2].starts_with("#1 Plan") && lines[2].ends_with("#2 Shipped"));
+        assert!(lines[3].ends_with('…'));
+        assert_eq!(lines[3].chars().count(), BOARD_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn story_page_shows_fields_and_body() {
+        let story = serde_json::json!({
+            "id": 7, "title": "Login", "status": "todo", "priority": "medium", "epic_id": null,
+            "labels": ["auth"], "blocked_by": [], "task_count": 0, "description": "Some notes",
+        });
+        let text = render(&View::Story, &Reply::from(story.clone()), Format::Plain);
+        assert_eq!(text, "#7 Login\nStatus: todo  Priority: medium\nLabels: auth\n\nSome notes");
+    }
+
+    #[test]
+    fn buffer_text_skips_wide_character_halves() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 8, 2));
+        buffer.set_string(0, 0, "Go 🚀 x", Style::default());
+        buffer.set_string(0, 1, "ab", Style::default().fg(Color::Red));
+        assert_eq!(buffer_text(&buffer, false), "Go 🚀 x\nab");
+        assert!(buffer_text(&buffer, true).contains("\u{1b}[38;5;1mab"));
+    }
+
+    #[test]
+    fn json_formats_wrap_the_result() {
+        let value = serde_json::json!({ "deleted": 3 });
+        assert_eq!(render(&View::Other, &Reply::from(value.clone()), Format::Json), r#"{"result":{"deleted":3}}"#);
+        assert!(render(&View::Other, &Reply::from(value.clone()), Format::Pretty).contains("\n  \"result\""));
+        assert_eq!(render(&View::Other, &Reply::from(value.clone()), Format::Table), "deleted: 3");
+        let error = StackError::not_found("story", 7);
+        assert_eq!(render_error(&error, Format::Plain), "error: story 7 not found");
+    }
+}
@@ -3,9 +3,9 @@ use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
-use tui_md;
 use crate::app::App;
-use crate::models::Status;
+use crate::color::epic_color;
+use crate::models::Sort;
 
 pub fn render(app: &App, frame: &mut Frame) {
     let chunks = Layout::default()
@@ -19,36 +19,73 @@ pub fn render(app: &App, frame: &mut Frame) {
         .split(frame.area());
 
     render_header(app, frame, chunks[0]);
-    render_columns(app, frame, chunks[1]);
+    render_columns(app, frame, chunks[1], true);
     render_preview(app, frame, chunks[2]);
     render_footer(app, frame, chunks[3]);
 }
 
+/// Just the header and columns, with nothing selected, for `stack board --render`.
+pub fn render_static(app: &App, frame: &mut Frame) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(frame.area());
+
+    render_header(app, frame, chunks[0]);
+    render_columns(app, frame, chunks[1], false);
+}
+
 fn render_header(app: &App, frame: &mut Frame, area: Rect) {
-    let epic_name = match app.epic_filter {
-        Some(eid) => app.epics.iter()
-            .find(|e| e.id == eid)
-            .map(|e| e.title.as_str())
-            .unwrap_or("Unknown"),
-        None => "All Epics",
+    let (epic_name, epic_style) = match app.epic_filter.and_then(|eid| app.epics.iter().find(|e| e.id == eid)) {
+        Some(epic) => (epic.title.as_str(), Style::default().fg(epic_color(&epic.color)).add_modifier(Modifier::BOLD)),
+        None if app.epic_filter.is_some() => ("Unknown", Style::default().fg(Color::White)),
+        None => ("All Epics", Style::default().fg(Color::White)),
     };
-    let header = Paragraph::new(Line::from(vec![
+    let board_name = app.boards.iter()
+        .find(|b| b.id == app.board_id)
+        .map(|b| b.name.as_str())
+        .unwrap_or("Unknown");
+    let mut spans = vec![
         Span::styled(" Stack ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
         Span::raw("│ "),
-        Span::styled(epic_name, Style::default().fg(Color::White)),
-    ]));
-    frame.render_widget(header, area);
+        Span::styled(board_name, Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+        Span::raw(" │ "),
+        Span::styled(epic_name, epic_style),
+    ];
+    if !app.filter.is_empty() {
+        spans.push(Span::raw(" │ "));
+        spans.push(Span::styled(format!("where {}", app.filter), Style::default().fg(Color::Green)));
+    }
+    if let Some(query) = &app.search_query {
+        spans.push(Span::raw(" │ "));
+        spans.push(Span::styled(
+            format!("/{} ({} matches)", query, app.search_hits.len()),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let db_label = format!("{} ", app.db_path);
+    let parts = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(db_label.chars().count() as u16)])
+        .split(area);
+
+    let header = Paragraph::new(Line::from(spans));
+    frame.render_widget(header, parts[0]);
+
+    let db_path = Paragraph::new(Span::styled(db_label, Style::default().fg(Color::DarkGray)));
+    frame.render_widget(db_path, parts[1]);
 }
 
-fn render_columns(app: &App, frame: &mut Frame, area: Rect) {
-    let col_constraints = vec![Constraint::Percentage(25); 4];
+fn render_columns(app: &App, frame: &mut Frame, area: Rect, show_selection: bool) {
+    let n = app.workflow.len().max(1) as u32;
+    let col_constraints = vec![Constraint::Ratio(1, n); app.workflow.len()];
     let col_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(col_constraints)
         .split(area);
 
-    for (i, status) in Status::all().iter().enumerate() {
-        let is_selected_col = i == app.selected_column;
+    for (i, column) in app.workflow.iter().enumerate() {
+        let is_selected_col = show_selection && i == app.selected_column;
         let border_style = if is_selected_col {
             Style::default().fg(Color::Cyan)
         } else {
@@ -64,22 +101,60 @@ fn render_columns(app: &App, frame: &mut Frame, area: Rect) {
                     crate::models::Priority::Medium => Color::Blue,
                     crate::models::Priority::Low => Color::DarkGray,
                 };
-                let line = Line::from(vec![
+                let mut spans = vec![
                     Span::styled(
                         format!(" {} ", story.priority.as_str().chars().next().unwrap_or('?')),
                         Style::default().fg(priority_color),
                     ),
                     Span::raw(&story.title),
-                ]);
-                ListItem::new(line)
+                ];
+                if let Some(claim) = &story.claim {
+                    spans.push(Span::styled(format!(" @{}", claim.owner), Style::default().fg(Color::Magenta)));
+                }
+                if story.blocked {
+                    spans.insert(1, Span::styled("⊘ ", Style::default().fg(Color::Red)));
+                }
+                if let Some(epic) = story.epic_id.and_then(|id| app.epics.iter().find(|e| e.id == id)) {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!(" {} ", epic.title.chars().take(12).collect::<String>()),
+                        Style::default().fg(Color::Black).bg(epic_color(&epic.color)),
+                    ));
+                }
+                if story.task_count > 0 {
+                    let color = if story.tasks_done == story.task_count { Color::Green } else { Color::DarkGray };
+                    spans.push(Span::styled(
+                        format!(" {}/{}", story.tasks_done, story.task_count),
+                        Style::default().fg(color),
+                    ));
+                }
+                for label in &story.labels {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!(" {} ", label),
+                        Style::default().fg(Color::Black).bg(Color::DarkGray),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
         let count = app.columns[i].len();
-        let title = format!("{} ({})", status.as_str(), count);
+        let mut title = match column.wip_limit {
+            Some(limit) => format!("{} ({}/{})", column.name, column.story_count, limit),
+            None => format!("{} ({})", column.name, count),
+        };
+        if column.sort != Sort::default() {
+            title.push_str(&format!(" {}", column.sort));
+        }
+        let title_style = if column.over_limit() {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
         let block = Block::default()
             .borders(Borders::ALL)
-            .title(title)
+            .title(Span::styled(title, title_style))
             .border_style(border_style);
 
         let highlight_style = if is_selected_col {
@@ -143,7 +218,8 @@ fn render_preview(app: &App, frame: &mut Frame, area: Rect) {
 
 fn render_footer(app: &App, frame: &mut Frame, area: Rect) {
     let hints = match app.mode {
-        crate::app::Mode::Board => "hjkl/↑↓←→: nav  a/s: move story  Enter: open  n: new  d: delete  e: epics  q: quit",
+        crate::app::Mode::Board if app.search_query.is_some() || !app.filter.is_empty() => "hjkl/↑↓←→: nav  a/s: move story  Enter: open  /: search  f: filter  Esc: clear search and filter  q: quit",
+        crate::app::Mode::Board => "hjkl/↑↓←→: nav  a/s: move story  J/K: reorder  Enter: open  n: new  d: delete  +/-: priority  E: set epic  e: epics  b: boards  /: search  f: filter  o: sort  u: undo  q: quit",
         _ => "",
     };
     let msg = if let Some(ref status) = app.status_message {
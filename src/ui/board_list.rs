@@ -0,0 +1,48 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
+use crate::app::App;
+
+pub fn render(app: &App, frame: &mut Frame) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(frame.area());
+
+    let header = Paragraph::new(Span::styled(
+        " Switch Board",
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    ));
+    frame.render_widget(header, chunks[0]);
+
+    let items: Vec<ListItem> = app.boards.iter().enumerate()
+        .map(|(i, board)| {
+            let style = if app.list_selection == i {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            let marker = if board.id == app.board_id { "●" } else { " " };
+            ListItem::new(Line::from(Span::styled(
+                format!(" {} {} ({})", marker, board.name, board.story_count),
+                style,
+            )))
+        })
+        .collect();
+
+    let block = Block::default().borders(Borders::ALL).title("Boards");
+    let list = List::new(items).block(block);
+    frame.render_widget(list, chunks[1]);
+
+    let footer = Paragraph::new(Span::styled(
+        "j/k: nav  Enter: switch  Esc: back",
+        Style::default().fg(Color::DarkGray),
+    ));
+    frame.render_widget(footer, chunks[2]);
+}
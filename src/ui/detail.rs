@@ -2,9 +2,10 @@ use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
-use tui_md;
-use crate::app::App;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use crate::app::{App, DetailFocus};
+use crate::color::epic_color;
+use crate::models::Status;
 
 pub fn render(app: &App, frame: &mut Frame) {
     let story = match &app.current_story {
@@ -25,9 +26,11 @@ pub fn render(app: &App, frame: &mut Frame) {
     let info = Paragraph::new(vec![
         Line::from(Span::styled(&story.title, Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan))),
         Line::from(vec![
-            Span::styled(format!("Status: {} ", story.status), Style::default().fg(Color::White)),
-            Span::styled(format!("Priority: {}", story.priority), Style::default().fg(Color::Yellow)),
+            Span::styled(format!("Status: {} ", story.status.name(&app.workflow)), Style::default().fg(Color::White)),
+            Span::styled(format!("Priority: {} ", story.priority), Style::default().fg(Color::Yellow)),
+            epic_span(app, story.epic_id),
         ]),
+        time_in_column_line(app),
     ])
     .block(Block::default().borders(Borders::ALL).title("Story"));
     frame.render_widget(info, chunks[0]);
@@ -42,16 +45,120 @@ pub fn render(app: &App, frame: &mut Frame) {
         tui_md::render(&story.description)
     };
 
+    let task_height = (app.tasks.len().max(1) as u16 + 2).min(12);
+    let panes = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(task_height)])
+        .split(chunks[1]);
+
     let body = Paragraph::new(body_lines)
-        .block(Block::default().borders(Borders::ALL).title("Body"))
+        .block(pane_block("Body".to_string(), app.detail_focus == DetailFocus::Body))
         .wrap(Wrap { trim: false })
         .scroll((app.scroll_offset, 0));
-    frame.render_widget(body, chunks[1]);
+    frame.render_widget(body, panes[0]);
+
+    render_tasks(app, frame, panes[1]);
 
     // Footer
+    let hints = match app.detail_focus {
+        DetailFocus::Body => "j/k/↑↓: scroll  Tab: tasks  t: add task  e: edit title  b: edit body  +/-: priority  E: epic  u: undo  Esc: back",
+        DetailFocus::Tasks => "j/k: select  Space: toggle  t: add  r: rename  d: delete  J/K: reorder  Tab: body  u: undo  Esc: back",
+    };
     let footer = Paragraph::new(Span::styled(
-        "j/k/↑↓: scroll  e: edit title  b: edit body  Esc: back",
+        app.status_message.as_deref().unwrap_or(hints),
         Style::default().fg(Color::DarkGray),
     ));
     frame.render_widget(footer, chunks[2]);
 }
+
+fn epic_span(app: &App, epic_id: Option<i64>) -> Span<'_> {
+    match epic_id.and_then(|id| app.epics.iter().find(|e| e.id == id)) {
+        Some(epic) => Span::styled(format!("Epic: {}", epic.title), Style::default().fg(epic_color(&epic.color))),
+        None => Span::styled("Epic: none", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+fn pane_block(title: String, focused: bool) -> Block<'static> {
+    let border_style = if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    Block::default().borders(Borders::ALL).title(title).border_style(border_style)
+}
+
+fn render_tasks(app: &App, frame: &mut Frame, area: ratatui::layout::Rect) {
+    let focused = app.detail_focus == DetailFocus::Tasks;
+    let done = app.tasks.iter().filter(|t| t.done).count();
+    let block = pane_block(format!("Tasks ({}/{})", done, app.tasks.len()), focused);
+
+    if app.tasks.is_empty() {
+        let empty = Paragraph::new(Span::styled(
+            "(no tasks — press 't' to add)",
+            Style::default().fg(Color::DarkGray),
+        ))
+        .block(block);
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = app.tasks.iter()
+        .map(|task| {
+            let (check, style) = if task.done {
+                ("[x] ", Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT))
+            } else {
+                ("[ ] ", Style::default())
+            };
+            ListItem::new(Line::from(vec![Span::raw(check), Span::styled(task.title.as_str(), style)]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default();
+    if focused {
+        state.select(Some(app.task_cursor));
+    }
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// "In column for 2h 5m" followed by the total time spent in each status visited.
+fn time_in_column_line(app: &App) -> Line<'_> {
+    let Some(current) = app.status_history.last() else {
+        return Line::default();
+    };
+
+    let mut totals: Vec<(&Status, i64)> = Vec::new();
+    for change in &app.status_history {
+        match totals.iter_mut().find(|(s, _)| **s == change.to_status) {
+            Some((_, secs)) => *secs += change.duration_secs,
+            None => totals.push((&change.to_status, change.duration_secs)),
+        }
+    }
+    let breakdown = totals.iter()
+        .map(|(status, secs)| format!("{} {}", status.name(&app.workflow), format_duration(*secs)))
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+    Line::from(vec![
+        Span::styled(
+            format!("In column for {} ", format_duration(current.duration_secs)),
+            Style::default().fg(Color::White),
+        ),
+        Span::styled(format!("({})", breakdown), Style::default().fg(Color::DarkGray)),
+    ])
+}
+
+fn format_duration(secs: i64) -> String {
+    let (days, hours, mins) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m", mins)
+    } else {
+        "<1m".to_string()
+    }
+}
@@ -4,6 +4,7 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
 use crate::app::App;
+use crate::color::epic_color;
 
 pub fn render(app: &App, frame: &mut Frame) {
     let chunks = Layout::default()
@@ -31,17 +32,22 @@ pub fn render(app: &App, frame: &mut Frame) {
     };
     items.push(ListItem::new(Line::from(Span::styled(" All Epics", all_style))));
 
+    let width = app.epics.iter().map(|e| e.title.chars().count()).max().unwrap_or(0);
     for (i, epic) in app.epics.iter().enumerate() {
         let is_selected = app.list_selection == i + 1;
         let style = if is_selected {
             Style::default().fg(Color::Black).bg(Color::Cyan)
         } else {
-            Style::default()
+            Style::default().fg(epic_color(&epic.color))
         };
-        items.push(ListItem::new(Line::from(Span::styled(
-            format!(" {}", epic.title),
-            style,
-        ))));
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:<width$}  ", epic.title, width = width), style),
+            progress_bar(epic.percent_complete()),
+            Span::styled(
+                format!(" {}/{} {}%", epic.done_count, epic.story_count, epic.percent_complete()),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])));
     }
 
     let block = Block::default().borders(Borders::ALL).title("Epics");
@@ -49,8 +55,19 @@ pub fn render(app: &App, frame: &mut Frame) {
     frame.render_widget(list, chunks[1]);
 
     let footer = Paragraph::new(Span::styled(
-        "j/k: nav  Enter: select  Esc: back",
+        app.status_message.as_deref()
+            .unwrap_or("j/k: nav  Enter: select  n: new  r: rename  c: color  d: delete  Esc: back"),
         Style::default().fg(Color::DarkGray),
     ));
     frame.render_widget(footer, chunks[2]);
 }
+
+const BAR_WIDTH: usize = 10;
+
+fn progress_bar(percent: i64) -> Span<'static> {
+    let filled = (percent.clamp(0, 100) as usize * BAR_WIDTH) / 100;
+    Span::styled(
+        format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled)),
+        Style::default().fg(Color::Green),
+    )
+}
@@ -1,26 +1,44 @@
 mod board;
+mod board_list;
 mod detail;
 mod epic_list;
 
 use ratatui::Frame;
 use crate::app::{App, Mode};
 
+pub use board::render_static as render_board;
+
 pub fn render(app: &App, frame: &mut Frame) {
     match app.mode {
         Mode::Board => board::render(app, frame),
         Mode::Detail => detail::render(app, frame),
         Mode::EpicList => epic_list::render(app, frame),
-        Mode::Input(_) => {
-            board::render(app, frame);
+        Mode::BoardList => board_list::render(app, frame),
+        Mode::EpicPicker => {
+            let origin = if app.current_story.is_some() { Mode::Detail } else { Mode::Board };
+            render_view(origin, app, frame);
+            render_epic_picker(app, frame);
+        }
+        Mode::Input(target) => {
+            render_view(target.origin(), app, frame);
             render_input_bar(app, frame);
         }
-        Mode::Confirm(_) => {
-            board::render(app, frame);
+        Mode::Confirm(action) => {
+            render_view(action.origin(), app, frame);
             render_confirm_bar(app, frame);
         }
     }
 }
 
+/// Render the view a dialog was opened from underneath it.
+fn render_view(mode: Mode, app: &App, frame: &mut Frame) {
+    match mode {
+        Mode::Detail => detail::render(app, frame),
+        Mode::EpicList => epic_list::render(app, frame),
+        _ => board::render(app, frame),
+    }
+}
+
 fn centered_dialog(frame: &mut Frame, width: u16, height: u16) -> ratatui::layout::Rect {
     use ratatui::layout::{Constraint, Layout, Direction};
 
@@ -43,13 +61,46 @@ fn render_input_bar(app: &App, frame: &mut Frame) {
 
     let area = centered_dialog(frame, 50, 3);
 
+    let title = match app.mode {
+        Mode::Input(crate::app::InputTarget::Search) => "Search (Enter to search, Esc to cancel)",
+        Mode::Input(crate::app::InputTarget::Filter) => "Filter, e.g. priority>=high status:todo",
+        Mode::Input(crate::app::InputTarget::NewTask) => "New task (Enter to add, Esc to cancel)",
+        Mode::Input(crate::app::InputTarget::NewEpic) => "New epic (Enter to create, Esc to cancel)",
+        _ => "Input (Enter to confirm, Esc to cancel)",
+    };
+
     frame.render_widget(Clear, area);
     let input = Paragraph::new(app.input_buffer.as_str())
-        .block(Block::default().borders(Borders::ALL).title("Input (Enter to confirm, Esc to cancel)").style(Style::default().bg(Color::Black).fg(Color::White)))
+        .block(Block::default().borders(Borders::ALL).title(title).style(Style::default().bg(Color::Black).fg(Color::White)))
         .style(Style::default().fg(Color::Yellow).bg(Color::Black));
     frame.render_widget(input, area);
 }
 
+fn render_epic_picker(app: &App, frame: &mut Frame) {
+    use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState};
+    use ratatui::style::{Color, Style};
+    use ratatui::text::Span;
+
+    let height = (app.epics.len() as u16 + 3).min(frame.area().height);
+    let area = centered_dialog(frame, 40, height);
+
+    let mut items = vec![ListItem::new(Span::styled(" No epic", Style::default().fg(Color::DarkGray)))];
+    items.extend(app.epics.iter().map(|epic| {
+        ListItem::new(Span::styled(
+            format!(" {}", epic.title),
+            Style::default().fg(crate::color::epic_color(&epic.color)),
+        ))
+    }));
+
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Epic (Enter: pick, Esc: cancel)").style(Style::default().bg(Color::Black).fg(Color::White)))
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    let mut state = ListState::default();
+    state.select(Some(app.list_selection));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
 fn render_confirm_bar(app: &App, frame: &mut Frame) {
     use ratatui::widgets::{Block, Borders, Paragraph, Clear};
     use ratatui::style::{Style, Color};
@@ -58,6 +109,7 @@ fn render_confirm_bar(app: &App, frame: &mut Frame) {
 
     let msg = match app.mode {
         Mode::Confirm(crate::app::ConfirmAction::DeleteStory) => "Delete this story? (y/n)",
+        Mode::Confirm(crate::app::ConfirmAction::DeleteEpic) => "Delete this epic? (y/n)",
         _ => "Confirm? (y/n)",
     };
 
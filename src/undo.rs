@@ -0,0 +1,287 @@
+use crate::db::Database;
+use crate::error::StackError;
+use crate::models::{EpicSnapshot, Sort, StoryPatch, StorySnapshot, Task};
+
+/// A single database change that the undo stack knows how to apply.
+#[derive(Debug, Clone)]
+pub enum Op {
+    /// Change story fields; the inverse restores the previous values.
+    Update { id: i64, patch: StoryPatch },
+    Delete { id: i64 },
+    Restore(Box<StorySnapshot>),
+    /// Move a story right before `anchor` on the board, or right after it.
+    /// Relative to a neighbour so it still holds after ranks are renumbered.
+    Rank { id: i64, anchor: i64, after: bool },
+    CreateTask { story_id: i64, title: String },
+    ToggleTask { id: i64 },
+    RenameTask { id: i64, title: String },
+    SwapTasks { a: i64, b: i64 },
+    DeleteTask { id: i64 },
+    RestoreTask(Task),
+    /// Change an epic's title or color; the inverse restores the previous ones.
+    UpdateEpic { id: i64, title: Option<String>, color: Option<String> },
+    DeleteEpic { id: i64 },
+    RestoreEpic(Box<EpicSnapshot>),
+    /// Show the cards of the column with id `column` in another order.
+    SortColumn { column: i64, sort: Sort },
+}
+
+impl Op {
+    /// Apply the change and return the op that reverses it.
+    fn apply(&self, db: &Database) -> Result<Op, StackError> {
+        Ok(match self {
+            Op::Update { id, patch } => {
+                let before = db.get_story(*id)?;
+                db.update_story(*id, patch)?;
+                Op::Update { id: *id, patch: StoryPatch {
+                    title: patch.title.as_ref().map(|_| before.title),
+                    description: patch.description.as_ref().map(|_| before.description),
+                    status: patch.status.as_ref().map(|_| before.status),
+                    priority: patch.priority.map(|_| before.priority),
+                    epic_id: patch.epic_id.map(|_| before.epic_id),
+                }}
+            }
+            Op::Delete { id } => {
+                let snapshot = db.story_snapshot(*id)?;
+                db.delete_story(*id)?;
+                Op::Restore(Box::new(snapshot))
+            }
+            Op::Restore(snapshot) => {
+                db.restore_story(snapshot)?;
+                Op::Delete { id: snapshot.story.id }
+            }
+            Op::Rank { id, anchor, after } => {
+                let (anchor, after) = db.rank_story(*id, *anchor, *after)?;
+                Op::Rank { id: *id, anchor, after }
+            }
+            Op::CreateTask { story_id, title } => {
+                Op::DeleteTask { id: db.create_task(*story_id, title)? }
+            }
+            Op::ToggleTask { id } => {
+                db.toggle_task(*id)?;
+                Op::ToggleTask { id: *id }
+            }
+            Op::RenameTask { id, title } => {
+                let before = db.get_task(*id)?.title;
+                db.rename_task(*id, title)?;
+                Op::RenameTask { id: *id, title: before }
+            }
+            Op::SwapTasks { a, b } => {
+                db.swap_tasks(*a, *b)?;
+                Op::SwapTasks { a: *a, b: *b }
+            }
+            Op::DeleteTask { id } => {
+                let task = db.get_task(*id)?;
+                db.delete_task(*id)?;
+                Op::RestoreTask(task)
+            }
+            Op::RestoreTask(task) => {
+                db.restore_task(task)?;
+                Op::DeleteTask { id: task.id }
+            }
+            Op::UpdateEpic { id, title, color } => {
+                let before = db.get_epic(*id)?;
+                db.update_epic(*id, title.as_deref(), None, color.as_deref())?;
+                Op::UpdateEpic {
+                    id: *id,
+                    title: title.as_ref().map(|_| before.title),
+                    color: color.as_ref().map(|_| before.color),
+                }
+            }
+            Op::DeleteEpic { id } => {
+                let snapshot = db.epic_snapshot(*id)?;
+                db.delete_epic(*id, None)?;
+                Op::RestoreEpic(Box::new(snapshot))
+            }
+            Op::RestoreEpic(snapshot) => {
+                db.restore_epic(snapshot)?;
+                Op::DeleteEpic { id: snapshot.epic.id }
+            }
+            Op::SortColumn { column, sort } => {
+                Op::SortColumn { column: *column, sort: db.set_column_sort(*column, *sort)? }
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    /// What the original mutation did, e.g. "move #3 to Done".
+    description: String,
+    /// Op that takes the database back to before (undo) or after (redo) it.
+    op: Op,
+}
+
+/// Undo/redo history for mutations made from the TUI.
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+}
+
+impl UndoStack {
+    /// Apply `op` and remember how to reverse it.
+    pub fn perform(&mut self, db: &Database, description: impl Into<String>, op: Op) -> Result<(), StackError> {
+        let inverse = op.apply(db)?;
+        self.record(description, inverse);
+        Ok(())
+    }
+
+    /// Remember how to reverse a mutation that was just applied.
+    pub fn record(&mut self, description: impl Into<String>, inverse: Op) {
+        self.undo.push(Entry { description: description.into(), op: inverse });
+        self.redo.clear();
+    }
+
+    /// Revert the latest mutation, returning its description. `None` if there
+    /// is nothing to undo. A failed undo is dropped from the history.
+    pub fn undo(&mut self, db: &Database) -> Option<Result<String, StackError>> {
+        let entry = self.undo.pop()?;
+        Some(entry.op.apply(db).map(|op| {
+            self.redo.push(Entry { description: entry.description.clone(), op });
+            entry.description
+        }))
+    }
+
+    /// Re-apply the latest undone mutation, returning its description.
+    pub fn redo(&mut self, db: &Database) -> Option<Result<String, StackError>> {
+        let entry = self.redo.pop()?;
+        Some(entry.op.apply(db).map(|op| {
+            self.undo.push(Entry { description: entry.description.clone(), op });
+            entry.description
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Priority, Status};
+
+    fn test_db() -> Database {
+        let mut db = Database::open(std::path::Path::new(":memory:")).unwrap();
+        db.migrate().unwrap();
+        db
+    }
+
+    #[test]
+    fn undo_and_redo_status_move() {
+        let db = test_db();
+        let id = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let mut stack = UndoStack::default();
+
+        let op = Op::Update { id, patch: StoryPatch { status: Some(Status::new("done")), ..Default::default() } };
+        stack.perform(&db, "move #1 to Done", op).unwrap();
+
+        assert_eq!(stack.undo(&db).unwrap().unwrap(), "move #1 to Done");
+        assert_eq!(db.get_story(id).unwrap().status, Status::new("todo"));
+        stack.redo(&db).unwrap().unwrap();
+        assert_eq!(db.get_story(id).unwrap().status, Status::new("done"));
+        assert!(stack.redo(&db).is_none());
+    }
+
+    #[test]
+    fn undo_rank_puts_story_back_beside_its_old_neighbour() {
+        let db = test_db();
+        let todo = Status::new("todo");
+        let [a, b, c] = ["A", "B", "C"].map(|t| db.create_story(t, "", None, &todo, Priority::Medium).unwrap());
+        let order = || -> Vec<i64> {
+            let stories = db.list_stories_by_status(&todo, None, &Default::default(), Default::default()).unwrap();
+            stories.iter().map(|s| s.id).collect()
+        };
+        let mut stack = UndoStack::default();
+
+        stack.perform(&db, "move #3 up", Op::Rank { id: c, anchor: a, after: false }).unwrap();
+        stack.perform(&db, "move #1 up", Op::Rank { id: a, anchor: c, after: false }).unwrap();
+        assert_eq!(order(), vec![a, c, b]);
+        stack.undo(&db).unwrap().unwrap();
+        assert_eq!(order(), vec![c, a, b]);
+        stack.undo(&db).unwrap().unwrap();
+        assert_eq!(order(), vec![a, b, c]);
+        stack.redo(&db).unwrap().unwrap();
+        assert_eq!(order(), vec![c, a, b]);
+    }
+
+    #[test]
+    fn undo_delete_restores_story() {
+        let db = test_db();
+        let id = db.create_story("S1", "body", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let mut stack = UndoStack::default();
+
+        stack.perform(&db, "delete #1", Op::Delete { id }).unwrap();
+        assert!(db.get_story(id).is_err());
+
+        stack.undo(&db).unwrap().unwrap();
+        assert_eq!(db.get_story(id).unwrap().description, "body");
+        stack.redo(&db).unwrap().unwrap();
+        assert!(db.get_story(id).is_err());
+    }
+
+    #[test]
+    fn undo_task_changes() {
+        let db = test_db();
+        let sid = db.create_story("S1", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let mut stack = UndoStack::default();
+
+        stack.perform(&db, "add task", Op::CreateTask { story_id: sid, title: "T1".to_string() }).unwrap();
+        let id = db.list_tasks(sid).unwrap()[0].id;
+        stack.perform(&db, "check task", Op::ToggleTask { id }).unwrap();
+        stack.perform(&db, "delete task", Op::DeleteTask { id }).unwrap();
+        assert!(db.list_tasks(sid).unwrap().is_empty());
+
+        stack.undo(&db).unwrap().unwrap();
+        assert!(db.get_task(id).unwrap().done);
+        stack.undo(&db).unwrap().unwrap();
+        assert!(!db.get_task(id).unwrap().done);
+        stack.undo(&db).unwrap().unwrap();
+        assert!(db.list_tasks(sid).unwrap().is_empty());
+    }
+
+    #[test]
+    fn undo_epic_delete_gives_stories_back() {
+        let db = test_db();
+        let epic = db.create_epic("Auth", "", "red").unwrap();
+        let id = db.create_story("S1", "", Some(epic), &Status::new("todo"), Priority::Medium).unwrap();
+        let mut stack = UndoStack::default();
+
+        stack.perform(&db, "recolor Auth", Op::UpdateEpic { id: epic, title: None, color: Some("blue".to_string()) }).unwrap();
+        stack.perform(&db, "delete Auth", Op::DeleteEpic { id: epic }).unwrap();
+        assert_eq!(db.get_story(id).unwrap().epic_id, None);
+
+        stack.undo(&db).unwrap().unwrap();
+        assert_eq!(db.get_story(id).unwrap().epic_id, Some(epic));
+        assert_eq!(db.get_epic(epic).unwrap().color, "blue");
+        stack.undo(&db).unwrap().unwrap();
+        assert_eq!(db.get_epic(epic).unwrap().color, "red");
+        stack.redo(&db).unwrap().unwrap();
+        stack.redo(&db).unwrap().unwrap();
+        assert!(db.get_epic(epic).is_err());
+    }
+
+    #[test]
+    fn undo_sort_restores_previous_order() {
+        let db = test_db();
+        let todo = Status::new("todo");
+        let column = db.get_column(&todo).unwrap().id;
+        let sort: Sort = "title".parse().unwrap();
+        let mut stack = UndoStack::default();
+
+        stack.perform(&db, "sort To Do by title", Op::SortColumn { column, sort }).unwrap();
+        assert_eq!(db.get_column(&todo).unwrap().sort, sort);
+        stack.undo(&db).unwrap().unwrap();
+        assert_eq!(db.get_column(&todo).unwrap().sort, Sort::default());
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let db = test_db();
+        let id = db.create_story("Old", "", None, &Status::new("todo"), Priority::Medium).unwrap();
+        let mut stack = UndoStack::default();
+
+        let op = Op::Update { id, patch: StoryPatch { title: Some("New".to_string()), ..Default::default() } };
+        stack.perform(&db, "rename #1", op).unwrap();
+        stack.undo(&db).unwrap().unwrap();
+        stack.record("create #2", Op::Delete { id: 2 });
+        assert!(stack.redo(&db).is_none());
+    }
+}